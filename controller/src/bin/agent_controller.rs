@@ -23,6 +23,13 @@
 //! - Creating and managing Kubernetes Jobs for agent execution
 //! - Handling resource cleanup and status updates
 //! - Providing health and metrics endpoints
+//!
+//! `agent-controller --check-templates [DIR] [--config PATH] [--fragments DIR]` renders
+//! every template (with any prompt fragments) in strict mode and exits non-zero on failure,
+//! without connecting to a cluster.
+//!
+//! `agent-controller --webhook-manifest [--namespace NS] [--service NAME] [--ca-file PATH]`
+//! prints the validating and mutating webhook configurations for the admission webhooks.
 
 use axum::{
     extract::State,
@@ -31,8 +38,15 @@ use axum::{
     routing::{get, post},
     Router,
 };
+use controller::leader::{LeaderElector, LeaderState};
+use controller::tasks::config::ControllerConfig;
 use controller::tasks::run_task_controller;
+use controller::tasks::template_check::check_templates;
+use controller::webhook::{self, WEBHOOK_CERT_DIR};
 use serde_json::{json, Value};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::signal;
 use tower::ServiceBuilder;
@@ -41,16 +55,112 @@ use tower_http::{
     timeout::TimeoutLayer,
     trace::{DefaultMakeSpan, DefaultOnRequest, DefaultOnResponse, TraceLayer},
 };
-use tracing::{info, Level};
+use tracing::{error, info, Level};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+/// Handle `--check-templates [DIR] [--config PATH] [--fragments DIR]`, returning the
+/// process exit code
+fn run_template_check(args: &[String]) -> i32 {
+    let mut template_dir = "/claude-templates";
+    let mut config_path = None;
+    let mut fragments_dir = None;
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--config" => config_path = rest.next().map(String::as_str),
+            "--fragments" => fragments_dir = rest.next().map(Path::new),
+            dir => template_dir = dir,
+        }
+    }
+
+    let config = match config_path {
+        Some(path) => match ControllerConfig::from_mounted_file(path) {
+            Ok(config) => config,
+            Err(e) => {
+                error!("{e}");
+                return 2;
+            }
+        },
+        None => ControllerConfig::default(),
+    };
+
+    let problems = check_templates(Path::new(template_dir), fragments_dir, &config);
+    if problems.is_empty() {
+        info!("All templates in {template_dir} rendered in strict mode");
+        return 0;
+    }
+    for problem in &problems {
+        error!("{problem}");
+    }
+    error!("{} template check(s) failed", problems.len());
+    1
+}
+
+/// Handle `--webhook-manifest [--namespace NS] [--service NAME] [--ca-file PATH]`,
+/// returning the process exit code
+fn run_webhook_manifest(args: &[String]) -> i32 {
+    let mut namespace = "agent-platform";
+    let mut service = "controller";
+    let mut ca_file = None;
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        let value = rest.next().map(String::as_str);
+        match (arg.as_str(), value) {
+            ("--namespace", Some(value)) => namespace = value,
+            ("--service", Some(value)) => service = value,
+            ("--ca-file", Some(value)) => ca_file = Some(value),
+            _ => {
+                let _ = writeln!(
+                    std::io::stderr(),
+                    "Unexpected argument for --webhook-manifest: {arg}"
+                );
+                return 2;
+            }
+        }
+    }
+
+    let ca_bundle = match ca_file.map(std::fs::read) {
+        Some(Ok(pem)) => Some(base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            pem,
+        )),
+        Some(Err(e)) => {
+            let _ = writeln!(std::io::stderr(), "Failed to read CA file: {e}");
+            return 2;
+        }
+        None => None,
+    };
+
+    let manifests = [
+        webhook::validating_webhook_configuration(namespace, service, ca_bundle.as_deref()),
+        webhook::mutating_webhook_configuration(namespace, service, ca_bundle.as_deref()),
+    ];
+    let documents: Result<Vec<String>, _> = manifests.iter().map(serde_yaml::to_string).collect();
+    match documents {
+        Ok(documents)
+            if std::io::stdout()
+                .write_all(documents.join("---\n").as_bytes())
+                .is_ok() =>
+        {
+            0
+        }
+        _ => 1,
+    }
+}
+
 #[derive(Clone)]
 struct AppState {
-    // Could be extended with shared state if needed
+    leader: LeaderState,
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Printed manifests go to stdout, so emit them before logging starts
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(pos) = args.iter().position(|arg| arg == "--webhook-manifest") {
+        std::process::exit(run_webhook_manifest(&args[pos + 1..]));
+    }
+
     // Initialize tracing
     tracing_subscriber::registry()
         .with(
@@ -65,18 +175,54 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         env!("CARGO_PKG_VERSION")
     );
 
+    if let Some(pos) = args.iter().position(|arg| arg == "--check-templates") {
+        std::process::exit(run_template_check(&args[pos + 1..]));
+    }
+
     // Initialize Kubernetes client and controller
     let client = kube::Client::try_default().await?;
     info!("Connected to Kubernetes cluster");
 
-    let state = AppState {};
+    // Only the lease holder runs the controllers; every replica serves HTTP
+    let identity = std::env::var("POD_NAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_else(|_| format!("controller-{}", std::process::id()));
+    let elector = LeaderElector::new(
+        client.clone(),
+        "agent-platform",
+        "agent-controller-leader",
+        &identity,
+    );
+    let state = AppState {
+        leader: elector.state(),
+    };
 
     // Start the controller in the background
     let controller_handle = {
         let client = client.clone();
         tokio::spawn(async move {
-            if let Err(e) = run_task_controller(client, "agent-platform".to_string()).await {
-                tracing::error!("Controller error: {}", e);
+            let result = elector
+                .run_while_leader(|| {
+                    run_task_controller(client.clone(), "agent-platform".to_string())
+                })
+                .await;
+            if let Err(e) = result {
+                error!("Controller error: {}", e);
+            }
+            // Exit rather than keep serving a healthy /health with no controllers running
+            error!("Controllers stopped, exiting");
+            std::process::exit(1);
+        })
+    };
+
+    // Every replica serves the admission webhook when a certificate is mounted
+    let webhook_handle = {
+        let config = Arc::new(
+            ControllerConfig::from_mounted_file("/config/config.yaml").unwrap_or_default(),
+        );
+        tokio::spawn(async move {
+            if let Err(e) = webhook::serve(config, Path::new(WEBHOOK_CERT_DIR)).await {
+                error!("Admission webhook error: {}", e);
             }
         })
     };
@@ -111,16 +257,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Wait for controller to finish
     controller_handle.abort();
+    webhook_handle.abort();
     info!("Controller service stopped");
 
     Ok(())
 }
 
-async fn health_check() -> Json<Value> {
+async fn health_check(State(state): State<AppState>) -> Json<Value> {
     Json(json!({
         "status": "healthy",
         "service": "controller",
-        "version": env!("CARGO_PKG_VERSION")
+        "version": env!("CARGO_PKG_VERSION"),
+        "leader": state.leader.is_leader()
     }))
 }
 
@@ -18,6 +18,46 @@ pub struct SecretEnvVar {
     pub secret_key: String,
 }
 
+/// Additional volume mounted into the agent container (e.g. a shared dependency cache)
+#[derive(Deserialize, Serialize, Clone, Debug, JsonSchema)]
+pub struct ExtraVolume {
+    /// Volume name, unique within the pod
+    pub name: String,
+    /// Absolute path to mount the volume at
+    #[serde(rename = "mountPath")]
+    pub mount_path: String,
+    /// Existing PVC to mount; an emptyDir is used when unset
+    #[serde(default, rename = "claimName")]
+    pub claim_name: Option<String>,
+    /// Mount the volume read-only
+    #[serde(default, rename = "readOnly")]
+    pub read_only: bool,
+}
+
+/// Helper container run in the agent's pod, e.g. a local tool server or a database
+#[derive(Deserialize, Serialize, Clone, Debug, JsonSchema)]
+pub struct Sidecar {
+    /// Container name, unique within the pod
+    pub name: String,
+    /// Container image
+    pub image: String,
+    /// Overrides the image entrypoint
+    #[serde(default)]
+    pub command: Vec<String>,
+    /// Arguments to the entrypoint
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Ports the sidecar listens on; the agent reaches them on localhost
+    #[serde(default)]
+    pub ports: Vec<u16>,
+    /// Environment variables for the sidecar
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Pod volumes to mount at the same paths as in the agent: `workspace` or an `extraVolumes` name
+    #[serde(default, rename = "sharedVolumes")]
+    pub shared_volumes: Vec<String>,
+}
+
 /// Default function for `context_version` field
 fn default_context_version() -> u32 {
     1
@@ -47,6 +87,10 @@ fn default_overwrite_memory() -> bool {
 #[kube(printcolumn = r#"{"name":"Service","type":"string","jsonPath":".spec.service"}"#)]
 #[kube(printcolumn = r#"{"name":"Model","type":"string","jsonPath":".spec.model"}"#)]
 #[kube(printcolumn = r#"{"name":"Phase","type":"string","jsonPath":".status.phase"}"#)]
+#[kube(printcolumn = r#"{"name":"Tests","type":"string","jsonPath":".status.resultDetail"}"#)]
+#[kube(
+    printcolumn = r#"{"name":"Message","type":"string","jsonPath":".status.message","priority":1}"#
+)]
 #[kube(printcolumn = r#"{"name":"Age","type":"date","jsonPath":".metadata.creationTimestamp"}"#)]
 pub struct CodeRunSpec {
     /// Task ID to implement
@@ -114,10 +158,180 @@ pub struct CodeRunSpec {
     /// Base64-encoded YAML containing task requirements (secrets and environment variables)
     #[serde(default, rename = "taskRequirements")]
     pub task_requirements: Option<String>,
+
+    /// Temporarily halt reconciliation without deleting the `CodeRun`
+    #[serde(default)]
+    pub paused: bool,
+
+    /// Preserve the workspace PVC untouched if the Job fails (defaults to false)
+    /// Other runs for the service wait until this CodeRun is deleted or its task is resubmitted
+    #[serde(default, rename = "keepWorkspace")]
+    pub keep_workspace: bool,
+
+    /// Shell commands run in the working directory before the agent starts
+    #[serde(default, rename = "initCommands")]
+    pub init_commands: Vec<String>,
+
+    /// Extra global git settings applied before cloning (e.g. `http.postBuffer`)
+    #[serde(default, rename = "gitConfig")]
+    pub git_config: HashMap<String, String>,
+
+    /// Additional PVC or emptyDir volumes mounted alongside the workspace
+    #[serde(default, rename = "extraVolumes")]
+    pub extra_volumes: Vec<ExtraVolume>,
+
+    /// Helper containers run alongside the agent in the same pod. They run as native
+    /// sidecars, which need Kubernetes 1.29 or later
+    #[serde(default)]
+    pub sidecars: Vec<Sidecar>,
+
+    /// Branch the feature branch is created from (defaults to the repository's default branch)
+    #[serde(default, rename = "baseBranch")]
+    pub base_branch: Option<String>,
+
+    /// Give the agent the tail of the last failed attempt's logs for this task and service
+    #[serde(default, rename = "sinceFailure")]
+    pub since_failure: bool,
+
+    /// Named tool permission preset from the controller's `permissions.profiles`
+    #[serde(default, rename = "toolsProfile")]
+    pub tools_profile: Option<String>,
+
+    /// Repository visibility hint ("public" or "private"); detected via the GitHub API when unset
+    #[serde(default, rename = "repositoryVisibility")]
+    pub repository_visibility: Option<String>,
+
+    /// Feature branch name template, e.g. `agent/{user}/task-{task_id}`; supports `{task_id}`,
+    /// `{service}`, `{user}` and `{timestamp}` (defaults to `feature/task-{task_id}-implementation`)
+    #[serde(default, rename = "workingBranch")]
+    pub working_branch: Option<String>,
+
+    /// Exact commit the feature branch starts from, checked out instead of the tip of the base
+    /// branch for reproducible runs; the base branch still sets where the PR targets
+    #[serde(default, rename = "commitSha")]
+    pub commit_sha: Option<String>,
+}
+
+impl CodeRunSpec {
+    /// Selected tool profile, treating an empty value (e.g. an unset workflow parameter) as none
+    pub fn selected_tools_profile(&self) -> Option<&str> {
+        self.tools_profile
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+    }
+
+    /// Repository visibility hint, treating an empty value as "detect at clone time"
+    pub fn repository_visibility_hint(&self) -> Option<&str> {
+        self.repository_visibility
+            .as_deref()
+            .map(str::trim)
+            .filter(|visibility| !visibility.is_empty())
+    }
+
+    /// Pinned commit, treating an empty value (e.g. an unset workflow parameter) as none
+    pub fn pinned_commit(&self) -> Option<&str> {
+        self.commit_sha
+            .as_deref()
+            .map(str::trim)
+            .filter(|sha| !sha.is_empty())
+    }
+
+    /// Render the branch the agent creates (or resumes) for this task from `workingBranch`,
+    /// else `default_template` (the controller's `defaults.workingBranch`).
+    /// `timestamp` fills `{timestamp}`; it should be stable across attempts of one `CodeRun`.
+    pub fn render_feature_branch(
+        &self,
+        timestamp: &str,
+        default_template: Option<&str>,
+    ) -> Result<String, String> {
+        let template = [self.working_branch.as_deref(), default_template]
+            .into_iter()
+            .flatten()
+            .map(str::trim)
+            .find(|template| !template.is_empty())
+            .unwrap_or(DEFAULT_WORKING_BRANCH);
+
+        let mut branch = String::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            branch.push_str(&rest[..start]);
+            let Some(len) = rest[start..].find('}') else {
+                return Err(format!("unclosed '{{' in branch template '{template}'"));
+            };
+            let value = match &rest[start + 1..start + len] {
+                "task_id" => self.task_id.to_string(),
+                "service" => self.service.clone(),
+                "user" => self
+                    .github_user
+                    .as_deref()
+                    .or(self.github_app.as_deref())
+                    .unwrap_or_default()
+                    .to_string(),
+                "timestamp" => timestamp.to_string(),
+                unknown => {
+                    return Err(format!(
+                        "unknown placeholder '{{{unknown}}}' in branch template '{template}'"
+                    ))
+                }
+            };
+            branch.push_str(&value);
+            rest = &rest[start + len + 1..];
+        }
+        branch.push_str(rest);
+
+        if is_valid_git_branch(&branch) {
+            Ok(branch)
+        } else {
+            Err(format!(
+                "branch template '{template}' renders to '{branch}', which is not a valid git branch name"
+            ))
+        }
+    }
+}
+
+const DEFAULT_WORKING_BRANCH: &str = "feature/task-{task_id}-implementation";
+
+/// Rules of `git check-ref-format --branch`
+fn is_valid_git_branch(name: &str) -> bool {
+    !name.is_empty()
+        && name != "@"
+        && !name.starts_with('-')
+        && !name.starts_with('/')
+        && !name.ends_with('/')
+        && !name.ends_with('.')
+        && !name.contains("..")
+        && !name.contains("//")
+        && !name.contains("@{")
+        && !name.chars().any(|c| {
+            c.is_ascii_control() || matches!(c, ' ' | '~' | '^' | ':' | '?' | '*' | '[' | '\\')
+        })
+        && name
+            .split('/')
+            .all(|part| !part.starts_with('.') && !part.ends_with(".lock"))
+}
+
+impl CodeRun {
+    /// Branch the agent creates (or resumes); `{timestamp}` is the `CodeRun`'s creation time.
+    /// `default_template` applies when the spec has no `workingBranch`, so the controller's
+    /// default holds without the admission webhook.
+    pub fn feature_branch(&self, default_template: Option<&str>) -> String {
+        let timestamp = self
+            .metadata
+            .creation_timestamp
+            .as_ref()
+            .map(|time| time.0.format("%Y%m%d%H%M%S").to_string())
+            .unwrap_or_default();
+        // Templates are validated before any Job starts, so this only guards against misuse
+        self.spec
+            .render_feature_branch(&timestamp, default_template)
+            .unwrap_or_else(|_| format!("feature/task-{}-implementation", self.spec.task_id))
+    }
 }
 
 /// Status of the `CodeRun`
 #[derive(Deserialize, Serialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
 pub struct CodeRunStatus {
     /// Current phase of the code implementation
     pub phase: String,
@@ -137,6 +351,48 @@ pub struct CodeRunStatus {
     /// Current retry attempt (if applicable)
     pub retry_count: Option<u32>,
 
+    /// Total number of Jobs started for this `CodeRun` across all context versions
+    #[serde(default, rename = "totalAttempts")]
+    pub total_attempts: Option<u32>,
+
+    /// Workspace PVC preserved for inspection after a failure (set when `keepWorkspace` is true)
+    #[serde(default, rename = "workspacePvc")]
+    pub workspace_pvc: Option<String>,
+
+    /// Branch the agent commits to, recorded when its Job starts
+    #[serde(default, rename = "gitBranch")]
+    pub git_branch: Option<String>,
+
+    /// Tool deny rules (including safe-mode rules) given to the agent, recorded when its Job starts
+    #[serde(default, rename = "toolsDeny")]
+    pub tools_deny: Option<Vec<String>>,
+
+    /// Outcome of the test check run after the agent finishes: `TestsPassed`, `TestsFailed`
+    /// or `NoTests`; unset when the check didn't report
+    #[serde(default, rename = "resultDetail")]
+    pub result_detail: Option<String>,
+
+    /// End of the agent's output when its Job failed, read before the Job's TTL removes
+    /// its pods; a `sinceFailure` retry hands it to the next attempt
+    #[serde(default, rename = "failureLogTail")]
+    pub failure_log_tail: Option<String>,
+
+    /// Retries of a reconcile that hit a transient Kubernetes API error; cleared on success
+    #[serde(default, rename = "reconcileRetries")]
+    pub reconcile_retries: Option<u32>,
+
+    /// When the next retry of a failed reconcile is due
+    #[serde(default, rename = "nextReconcileAt")]
+    pub next_reconcile_at: Option<String>,
+
+    /// Spec generation the controller last saw before starting a Job
+    #[serde(default, rename = "observedGeneration")]
+    pub observed_generation: Option<i64>,
+
+    /// When `observedGeneration` was first seen; starts the `specSettleSeconds` window
+    #[serde(default, rename = "generationObservedAt")]
+    pub generation_observed_at: Option<String>,
+
     /// Conditions for the `CodeRun`
     pub conditions: Option<Vec<CodeRunCondition>>,
 
@@ -25,6 +25,80 @@ pub struct DocsRunSpec {
     pub github_app: Option<String>,
     #[serde(rename = "includeCodebase", default)]
     pub include_codebase: Option<bool>,
+    /// Temporarily halt reconciliation without deleting the `DocsRun`
+    #[serde(default)]
+    pub paused: bool,
+    /// Markup format for the generated task documentation (defaults to markdown)
+    #[serde(rename = "outputFormat", default)]
+    pub output_format: DocsOutputFormat,
+    /// Only regenerate docs for tasks changed since the last successful `DocsRun`
+    #[serde(rename = "changedOnly", default)]
+    pub changed_only: bool,
+    /// Repo-relative path to the architecture doc (defaults to `<taskmasterDir>/docs/architecture.md`)
+    #[serde(rename = "architectureFile", default)]
+    pub architecture_file: Option<String>,
+    /// Repo-relative path to the PRD (defaults to `<taskmasterDir>/docs/prd.txt`)
+    #[serde(rename = "prdFile", default)]
+    pub prd_file: Option<String>,
+    /// Task Master directory relative to `workingDirectory` (defaults to `.taskmaster`)
+    #[serde(rename = "taskmasterDir", default)]
+    pub taskmaster_dir: Option<String>,
+    /// How `promptModification` combines with the generated prompt (defaults to append)
+    #[serde(rename = "promptMode", default)]
+    pub prompt_mode: PromptMode,
+    /// Corrective guidance for a retry, e.g. what the previous run missed
+    #[serde(rename = "promptModification", default)]
+    pub prompt_modification: Option<String>,
+}
+
+/// How retry guidance is applied to the docs prompt
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptMode {
+    /// Add the guidance after the generated prompt
+    #[default]
+    Append,
+    /// Use the guidance as the whole prompt
+    Replace,
+}
+
+impl PromptMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PromptMode::Append => "append",
+            PromptMode::Replace => "replace",
+        }
+    }
+}
+
+/// Markup format the docs agent writes task documentation in
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DocsOutputFormat {
+    #[default]
+    Markdown,
+    Rst,
+    Asciidoc,
+}
+
+impl DocsOutputFormat {
+    /// File extension used for generated documentation files
+    pub fn extension(self) -> &'static str {
+        match self {
+            DocsOutputFormat::Markdown => "md",
+            DocsOutputFormat::Rst => "rst",
+            DocsOutputFormat::Asciidoc => "adoc",
+        }
+    }
+
+    /// Human-readable format name used in the agent prompt
+    pub fn display_name(self) -> &'static str {
+        match self {
+            DocsOutputFormat::Markdown => "Markdown",
+            DocsOutputFormat::Rst => "reStructuredText",
+            DocsOutputFormat::Asciidoc => "AsciiDoc",
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug, JsonSchema)]
@@ -40,6 +114,18 @@ pub struct DocsRunStatus {
     /// Tracks whether the documentation work has been completed successfully
     /// This field is used for idempotent reconciliation and TTL safety
     pub work_completed: Option<bool>,
+    /// Fingerprint of the spec inputs that determine the generated docs
+    pub input_hash: Option<String>,
+    /// Name of the earlier `DocsRun` whose result was reused instead of running a job
+    pub reused_from: Option<String>,
+    /// Commit the documentation was generated from, used as the marker for incremental runs
+    pub source_commit: Option<String>,
+    /// Marker commit an incremental run compared `tasks.json` against
+    pub changed_since: Option<String>,
+    /// Retries of a reconcile that hit a transient Kubernetes API error; cleared on success
+    pub reconcile_retries: Option<u32>,
+    /// When the next retry of a failed reconcile is due
+    pub next_reconcile_at: Option<String>,
 }
 
 /// Condition for the `DocsRun`
@@ -0,0 +1,70 @@
+//! `IntakeRun` Custom Resource Definition for turning a PRD into Task Master tasks
+
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Default function for `num_tasks` field
+fn default_num_tasks() -> u32 {
+    50
+}
+
+/// Default function for `source_branch` field
+fn default_source_branch() -> String {
+    "main".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(CustomResource, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+#[kube(group = "agents.platform", version = "v1", kind = "IntakeRun")]
+#[kube(namespaced)]
+#[kube(status = "IntakeRunStatus")]
+#[kube(printcolumn = r#"{"name":"Project","type":"string","jsonPath":".spec.projectName"}"#)]
+#[kube(printcolumn = r#"{"name":"Phase","type":"string","jsonPath":".status.phase"}"#)]
+#[kube(printcolumn = r#"{"name":"Age","type":"date","jsonPath":".metadata.creationTimestamp"}"#)]
+pub struct IntakeRunSpec {
+    /// Project directory the generated Task Master project is written to
+    #[serde(rename = "projectName")]
+    pub project_name: String,
+    /// Repository the intake PR is opened against
+    #[serde(rename = "repositoryUrl")]
+    pub repository_url: String,
+    /// Branch the intake PR is based on
+    #[serde(rename = "sourceBranch", default = "default_source_branch")]
+    pub source_branch: String,
+    /// GitHub App name for authentication (e.g., "5DLabs-Morgan")
+    #[serde(rename = "githubApp")]
+    pub github_app: String,
+    /// Claude model used to parse the PRD
+    pub model: String,
+    /// Target number of top-level tasks
+    #[serde(rename = "numTasks", default = "default_num_tasks")]
+    pub num_tasks: u32,
+    /// Whether to expand tasks into subtasks
+    #[serde(rename = "expandTasks", default = "default_true")]
+    pub expand_tasks: bool,
+    /// Whether to run the complexity analysis
+    #[serde(rename = "analyzeComplexity", default = "default_true")]
+    pub analyze_complexity: bool,
+    /// Product requirements document content
+    #[serde(rename = "prdContent")]
+    pub prd_content: String,
+    /// Optional architecture document content
+    #[serde(rename = "architectureContent", default)]
+    pub architecture_content: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IntakeRunStatus {
+    pub phase: String,
+    pub message: Option<String>,
+    pub last_update: Option<String>,
+    pub job_name: Option<String>,
+    pub configmap_name: Option<String>,
+    /// Tracks whether the intake has completed successfully, for idempotent reconciliation
+    pub work_completed: Option<bool>,
+}
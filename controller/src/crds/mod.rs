@@ -1,5 +1,7 @@
 pub mod coderun;
 pub mod docsrun;
+pub mod intakerun;
 
 pub use coderun::*;
 pub use docsrun::*;
+pub use intakerun::*;
@@ -0,0 +1,295 @@
+//! Lease-based leader election
+//!
+//! Only the replica holding the `coordination.k8s.io` Lease runs the CodeRun/DocsRun
+//! controllers, so multiple replicas don't race on Job creation. Every replica keeps
+//! serving HTTP.
+
+use k8s_openapi::api::coordination::v1::{Lease, LeaseSpec};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::{MicroTime, ObjectMeta};
+use kube::api::{Api, PostParams};
+use kube::Client;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// How long a lease is valid without renewal
+const LEASE_DURATION: Duration = Duration::from_secs(15);
+/// How often the leader renews, and how often followers retry acquisition
+const RETRY_PERIOD: Duration = Duration::from_secs(5);
+
+/// Shared view of whether this replica currently holds the lease
+#[derive(Clone, Default)]
+pub struct LeaderState(Arc<AtomicBool>);
+
+impl LeaderState {
+    pub fn is_leader(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn set(&self, leader: bool) {
+        self.0.store(leader, Ordering::Relaxed);
+    }
+}
+
+/// Acquires and renews a named Lease on behalf of this replica
+pub struct LeaderElector {
+    leases: Api<Lease>,
+    lease_name: String,
+    identity: String,
+    state: LeaderState,
+    retry_period: Duration,
+}
+
+impl LeaderElector {
+    pub fn new(client: Client, namespace: &str, lease_name: &str, identity: &str) -> Self {
+        Self {
+            leases: Api::namespaced(client, namespace),
+            lease_name: lease_name.to_string(),
+            identity: identity.to_string(),
+            state: LeaderState::default(),
+            retry_period: RETRY_PERIOD,
+        }
+    }
+
+    /// Handle for reporting leadership (e.g. in `/health`)
+    pub fn state(&self) -> LeaderState {
+        self.state.clone()
+    }
+
+    /// Wait until this replica holds the lease
+    pub async fn acquire(&self) {
+        loop {
+            if self.try_acquire_or_renew().await {
+                info!(
+                    "Acquired leader lease {} as {}",
+                    self.lease_name, self.identity
+                );
+                return;
+            }
+            debug!(
+                "Lease {} held by another replica, retrying",
+                self.lease_name
+            );
+            tokio::time::sleep(self.retry_period).await;
+        }
+    }
+
+    /// Keep renewing the lease; returns once leadership has been lost
+    pub async fn hold(&self) {
+        loop {
+            tokio::time::sleep(self.retry_period).await;
+            if !self.try_acquire_or_renew().await {
+                warn!("Lost leader lease {} as {}", self.lease_name, self.identity);
+                return;
+            }
+        }
+    }
+
+    /// Run `lead` each time this replica acquires the lease, dropping it as soon as the
+    /// lease is lost. Returns `lead`'s output once it finishes while still leader.
+    ///
+    /// Anything `lead` starts must stop when its future is dropped (e.g. tasks owned by a
+    /// `JoinSet`), or a replica that lost the lease keeps acting as leader.
+    pub async fn run_while_leader<F, Fut, T>(&self, mut lead: F) -> T
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        loop {
+            self.acquire().await;
+            tokio::select! {
+                output = lead() => return output,
+                () = self.hold() => {
+                    warn!("Leadership lost, stopping controllers until re-elected");
+                }
+            }
+        }
+    }
+
+    async fn try_acquire_or_renew(&self) -> bool {
+        let leader = match self.update_lease().await {
+            Ok(leader) => leader,
+            Err(e) => {
+                warn!("Leader election error on lease {}: {}", self.lease_name, e);
+                false
+            }
+        };
+        self.state.set(leader);
+        leader
+    }
+
+    async fn update_lease(&self) -> kube::Result<bool> {
+        let now = chrono::Utc::now();
+
+        let Some(mut lease) = self.leases.get_opt(&self.lease_name).await? else {
+            let lease = Lease {
+                metadata: ObjectMeta {
+                    name: Some(self.lease_name.clone()),
+                    ..Default::default()
+                },
+                spec: Some(self.lease_spec(now, 0, None)),
+            };
+            return match self.leases.create(&PostParams::default(), &lease).await {
+                Ok(_) => Ok(true),
+                Err(kube::Error::Api(response)) if response.code == 409 => Ok(false),
+                Err(e) => Err(e),
+            };
+        };
+
+        let spec = lease.spec.clone().unwrap_or_default();
+        let held_by_us = spec.holder_identity.as_deref() == Some(self.identity.as_str());
+        let expired = spec.renew_time.as_ref().is_none_or(|renewed| {
+            let duration = spec
+                .lease_duration_seconds
+                .map_or(LEASE_DURATION.as_secs() as i64, i64::from);
+            now - renewed.0 > chrono::Duration::seconds(duration)
+        });
+
+        if !held_by_us && !expired {
+            return Ok(false);
+        }
+
+        let transitions = spec.lease_transitions.unwrap_or(0) + i32::from(!held_by_us);
+        let acquired = if held_by_us { spec.acquire_time } else { None };
+        lease.spec = Some(self.lease_spec(now, transitions, acquired));
+
+        // resourceVersion on the fetched lease makes this a compare-and-swap
+        match self
+            .leases
+            .replace(&self.lease_name, &PostParams::default(), &lease)
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(kube::Error::Api(response)) if response.code == 409 => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn lease_spec(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        transitions: i32,
+        acquire_time: Option<MicroTime>,
+    ) -> LeaseSpec {
+        LeaseSpec {
+            holder_identity: Some(self.identity.clone()),
+            lease_duration_seconds: Some(LEASE_DURATION.as_secs() as i32),
+            acquire_time: acquire_time.or(Some(MicroTime(now))),
+            renew_time: Some(MicroTime(now)),
+            lease_transitions: Some(transitions),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::testing::FakeApiServer;
+    use serde_json::json;
+    use std::sync::Mutex;
+    use tokio::sync::oneshot;
+    use tokio::task::JoinSet;
+
+    const LEASE_PATH: &str =
+        "/apis/coordination.k8s.io/v1/namespaces/agent-platform/leases/agent-controller-leader";
+
+    fn lease_held_by(holder: &str, renewed: chrono::DateTime<chrono::Utc>) -> serde_json::Value {
+        json!({
+            "apiVersion": "coordination.k8s.io/v1",
+            "kind": "Lease",
+            "metadata": { "name": "agent-controller-leader", "resourceVersion": "7" },
+            "spec": {
+                "holderIdentity": holder,
+                "leaseDurationSeconds": 15,
+                "renewTime": renewed.to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+                "leaseTransitions": 1
+            }
+        })
+    }
+
+    async fn eventually(mut done: impl FnMut() -> bool) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !done() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("condition not reached in time");
+    }
+
+    #[tokio::test]
+    async fn test_losing_the_lease_stops_controllers_until_reacquired() {
+        let api = FakeApiServer::start().await;
+        let mut elector = LeaderElector::new(
+            api.client("agent-platform"),
+            "agent-platform",
+            "agent-controller-leader",
+            "controller-a",
+        );
+        elector.retry_period = Duration::from_millis(20);
+        let state = elector.state();
+
+        // Each term spawns a stand-in controller that only ends when it is aborted
+        let stopped = Arc::new(Mutex::new(Vec::new()));
+        let terms = {
+            let stopped = stopped.clone();
+            tokio::spawn(async move {
+                let mut term = 0;
+                elector
+                    .run_while_leader(|| {
+                        term += 1;
+                        let (running, stop) = oneshot::channel::<()>();
+                        stopped.lock().unwrap().push(stop);
+                        let term = term;
+                        async move {
+                            let mut controllers = JoinSet::new();
+                            controllers.spawn(async move {
+                                let _running = running;
+                                std::future::pending::<()>().await
+                            });
+                            if term == 1 {
+                                controllers.join_next().await;
+                            }
+                            term
+                        }
+                    })
+                    .await
+            })
+        };
+
+        // First term: the lease doesn't exist yet, so it is created
+        eventually(|| state.is_leader()).await;
+        assert_eq!(
+            api.get(LEASE_PATH).unwrap()["spec"]["holderIdentity"],
+            "controller-a"
+        );
+
+        // Another replica takes over; the first term's controller must be aborted
+        api.insert(
+            LEASE_PATH,
+            lease_held_by("controller-b", chrono::Utc::now()),
+        );
+        let first = stopped.lock().unwrap().remove(0);
+        tokio::time::timeout(Duration::from_secs(5), first)
+            .await
+            .expect("controller kept running after the lease was lost")
+            .unwrap_err();
+        assert!(!state.is_leader());
+
+        // Once the other replica's lease expires, a new term starts
+        let expired = chrono::Utc::now() - chrono::Duration::seconds(60);
+        api.insert(LEASE_PATH, lease_held_by("controller-b", expired));
+        let term = tokio::time::timeout(Duration::from_secs(5), terms)
+            .await
+            .expect("lease was not re-acquired")
+            .unwrap();
+        assert_eq!(term, 2);
+        assert!(state.is_leader());
+
+        let lease = api.get(LEASE_PATH).unwrap();
+        assert_eq!(lease["spec"]["holderIdentity"], "controller-a");
+        assert_eq!(lease["spec"]["leaseTransitions"], 2);
+    }
+}
@@ -22,8 +22,13 @@
 //! including Kubernetes client wrapper, job orchestration, and request handling.
 
 pub mod crds;
+pub mod leader;
 pub mod tasks;
+pub mod webhook;
 
 // Re-export commonly used types
-pub use crds::{CodeRun, CodeRunSpec, CodeRunStatus, DocsRun, DocsRunSpec, DocsRunStatus};
+pub use crds::{
+    CodeRun, CodeRunSpec, CodeRunStatus, DocsRun, DocsRunSpec, DocsRunStatus, IntakeRun,
+    IntakeRunSpec, IntakeRunStatus,
+};
 pub use tasks::config::ControllerConfig;
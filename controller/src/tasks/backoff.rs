@@ -0,0 +1,259 @@
+//! Bounded exponential backoff for failed CodeRun and DocsRun reconciles
+//!
+//! Transient Kubernetes API errors (conflicts, throttling, server errors, dropped
+//! connections) requeue the run with a doubling delay, up to `job.maxReconcileRetries`
+//! times. Each retry is recorded in `status.reconcileRetries` and `status.nextReconcileAt`,
+//! which also holds off the reconcile triggered by that status patch until the delay has
+//! passed. Permanent errors, or running out of retries, wait for the resource to change.
+
+use crate::crds::{CodeRun, DocsRun};
+use crate::tasks::types::{Context, Error, Result};
+use k8s_openapi::NamespaceResourceScope;
+use kube::api::{Patch, PatchParams};
+use kube::runtime::controller::Action;
+use kube::{Api, Resource, ResourceExt};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use std::fmt::Debug;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Delay before the first retry; doubles with each further retry
+const BASE_DELAY: Duration = Duration::from_secs(5);
+
+/// Longest delay between retries
+const MAX_DELAY: Duration = Duration::from_secs(300);
+
+/// Retry bookkeeping shared by the CodeRun and DocsRun statuses
+pub(crate) trait RetryStatus {
+    fn has_status(&self) -> bool;
+    fn reconcile_retries(&self) -> u32;
+    fn next_reconcile_at(&self) -> Option<&str>;
+}
+
+impl RetryStatus for CodeRun {
+    fn has_status(&self) -> bool {
+        self.status.is_some()
+    }
+
+    fn reconcile_retries(&self) -> u32 {
+        self.status
+            .as_ref()
+            .and_then(|s| s.reconcile_retries)
+            .unwrap_or(0)
+    }
+
+    fn next_reconcile_at(&self) -> Option<&str> {
+        self.status.as_ref()?.next_reconcile_at.as_deref()
+    }
+}
+
+impl RetryStatus for DocsRun {
+    fn has_status(&self) -> bool {
+        self.status.is_some()
+    }
+
+    fn reconcile_retries(&self) -> u32 {
+        self.status
+            .as_ref()
+            .and_then(|s| s.reconcile_retries)
+            .unwrap_or(0)
+    }
+
+    fn next_reconcile_at(&self) -> Option<&str> {
+        self.status.as_ref()?.next_reconcile_at.as_deref()
+    }
+}
+
+/// Delay before retrying after `error`, or `None` to stop retrying
+pub(crate) fn retry_delay(error: &Error, retries: u32, max_retries: u32) -> Option<Duration> {
+    if !error.is_transient() || retries >= max_retries {
+        return None;
+    }
+    let factor = 2u32.saturating_pow(retries);
+    Some(BASE_DELAY.saturating_mul(factor).min(MAX_DELAY))
+}
+
+/// Time left before a scheduled retry, if one is pending
+fn remaining_wait(run: &impl RetryStatus, now: chrono::DateTime<chrono::Utc>) -> Option<Duration> {
+    let next = chrono::DateTime::parse_from_rfc3339(run.next_reconcile_at()?).ok()?;
+    (next.with_timezone(&chrono::Utc) - now)
+        .to_std()
+        .ok()
+        .filter(|wait| !wait.is_zero())
+}
+
+/// Run `reconcile` unless a retry is still pending, clearing the retry count once it succeeds
+pub(crate) async fn reconcile_with_backoff<K, F, Fut>(
+    run: Arc<K>,
+    ctx: Arc<Context>,
+    reconcile: F,
+) -> Result<Action>
+where
+    K: Resource<DynamicType = (), Scope = NamespaceResourceScope>
+        + RetryStatus
+        + Clone
+        + DeserializeOwned
+        + Debug,
+    F: FnOnce(Arc<K>, Arc<Context>) -> Fut,
+    Fut: Future<Output = Result<Action>>,
+{
+    if let Some(wait) = remaining_wait(run.as_ref(), chrono::Utc::now()) {
+        return Ok(Action::requeue(wait));
+    }
+
+    let retries = run.reconcile_retries();
+    let action = reconcile(run.clone(), ctx.clone()).await?;
+    // A deleted run may already be gone once its finalizer is removed
+    if retries > 0 && run.meta().deletion_timestamp.is_none() {
+        info!(
+            "{} {} reconciled after {} retries",
+            K::kind(&()),
+            run.name_any(),
+            retries
+        );
+        let status = json!({ "reconcileRetries": null, "nextReconcileAt": null });
+        patch_retry_status(run.as_ref(), &ctx, status).await?;
+    }
+    Ok(action)
+}
+
+/// Error policy for the CodeRun and DocsRun controllers
+pub(crate) fn error_policy<K>(run: Arc<K>, error: &Error, ctx: Arc<Context>) -> Action
+where
+    K: Resource<DynamicType = (), Scope = NamespaceResourceScope>
+        + RetryStatus
+        + Clone
+        + DeserializeOwned
+        + Debug,
+    K: Send + Sync + 'static,
+{
+    let kind = K::kind(&());
+    let name = run.name_any();
+    let retries = run.reconcile_retries();
+    let max_retries = ctx.config.job.max_reconcile_retries;
+
+    let Some(delay) = retry_delay(error, retries, max_retries) else {
+        if error.is_transient() {
+            error!(error = ?error, "{kind} {name} still failing after {retries} retries, stopping");
+        } else {
+            error!(error = ?error, "{kind} {name} reconciliation failed, stopping");
+        }
+        return Action::await_change();
+    };
+
+    warn!(
+        error = ?error,
+        "{kind} {name} reconciliation failed, retry {}/{max_retries} in {}s",
+        retries + 1,
+        delay.as_secs()
+    );
+    let next = chrono::Utc::now() + delay;
+    let mut status = json!({
+        "reconcileRetries": retries + 1,
+        "nextReconcileAt": next.to_rfc3339(),
+    });
+    if !run.has_status() {
+        // phase is required, and a brand-new run has no status yet
+        status["phase"] = json!("Pending");
+    }
+    tokio::spawn(async move {
+        if let Err(e) = patch_retry_status(run.as_ref(), &ctx, status).await {
+            warn!(
+                "Failed to record retry for {} {}: {e}",
+                K::kind(&()),
+                run.name_any()
+            );
+        }
+    });
+    Action::requeue(delay)
+}
+
+async fn patch_retry_status<K>(run: &K, ctx: &Context, status: serde_json::Value) -> Result<()>
+where
+    K: Resource<DynamicType = (), Scope = NamespaceResourceScope>
+        + Clone
+        + DeserializeOwned
+        + Debug,
+{
+    let api: Api<K> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
+    api.patch_status(
+        &run.name_any(),
+        &PatchParams::default(),
+        &Patch::Merge(&json!({ "status": status })),
+    )
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kube::core::ErrorResponse;
+
+    fn api_error(code: u16) -> Error {
+        Error::KubeError(kube::Error::Api(ErrorResponse {
+            status: "Failure".to_string(),
+            message: String::new(),
+            reason: String::new(),
+            code,
+        }))
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off_transient_errors_only() {
+        assert_eq!(
+            retry_delay(&api_error(429), 0, 5),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            retry_delay(&api_error(503), 2, 5),
+            Some(Duration::from_secs(20))
+        );
+        assert_eq!(retry_delay(&api_error(409), 10, 20), Some(MAX_DELAY));
+        assert_eq!(retry_delay(&api_error(500), 5, 5), None);
+
+        assert_eq!(retry_delay(&api_error(403), 0, 5), None);
+        assert_eq!(
+            retry_delay(&Error::ConfigError("bad spec".to_string()), 0, 5),
+            None
+        );
+    }
+
+    #[test]
+    fn test_pending_retry_holds_off_reconcile() {
+        let now = chrono::Utc::now();
+        let mut code_run: CodeRun = serde_json::from_value(json!({
+            "apiVersion": "agents.platform/v1",
+            "kind": "CodeRun",
+            "metadata": { "name": "task-5" },
+            "spec": {
+                "taskId": 5,
+                "service": "api",
+                "repositoryUrl": "https://github.com/5dlabs/example",
+                "docsRepositoryUrl": "https://github.com/5dlabs/docs",
+                "model": "claude-sonnet-4-20250514"
+            },
+            "status": {
+                "phase": "Running",
+                "reconcileRetries": 2,
+                "nextReconcileAt": (now + chrono::Duration::seconds(20)).to_rfc3339()
+            }
+        }))
+        .unwrap();
+        assert_eq!(code_run.reconcile_retries(), 2);
+        assert_eq!(
+            remaining_wait(&code_run, now),
+            Some(Duration::from_secs(20))
+        );
+        assert_eq!(
+            remaining_wait(&code_run, now + chrono::Duration::seconds(20)),
+            None
+        );
+
+        code_run.status.as_mut().unwrap().next_reconcile_at = None;
+        assert_eq!(remaining_wait(&code_run, now), None);
+    }
+}
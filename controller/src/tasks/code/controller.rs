@@ -1,17 +1,22 @@
-use super::resources::CodeResourceManager;
-use crate::crds::CodeRun;
-use crate::tasks::types::{Context, Result, CODE_FINALIZER_NAME};
+use super::resources::{failure_log_tail, CodeResourceManager, PREVIOUS_FAILURE_LOG_LINES};
+use super::templates::CodeTemplateGenerator;
+use crate::crds::{CodeRun, CodeRunCondition, CodeRunSpec};
+use crate::tasks::config::{CaBundleConfig, PermissionsConfig};
+use crate::tasks::types::{
+    job_needs_adoption, resolve_job_name, resource_name, Context, KubeResultExt, Result,
+    CODE_FINALIZER_NAME, PRESERVED_WORKSPACE_ANNOTATION,
+};
 use k8s_openapi::api::{
     batch::v1::Job,
-    core::v1::{ConfigMap, PersistentVolumeClaim},
+    core::v1::{ConfigMap, PersistentVolumeClaim, Pod},
 };
-use kube::api::{Patch, PatchParams};
+use kube::api::{DeleteParams, ListParams, LogParams, Patch, PatchParams, PropagationPolicy};
 use kube::runtime::controller::Action;
 use kube::runtime::finalizer::{finalizer, Event as FinalizerEvent};
 use kube::{Api, ResourceExt};
 use serde_json::json;
 use std::sync::Arc;
-use tracing::{info, instrument};
+use tracing::{info, instrument, warn};
 
 #[instrument(skip(ctx), fields(code_run_name = %code_run.name_any(), namespace = %ctx.namespace))]
 pub async fn reconcile_code_run(code_run: Arc<CodeRun>, ctx: Arc<Context>) -> Result<Action> {
@@ -28,6 +33,12 @@ pub async fn reconcile_code_run(code_run: Arc<CodeRun>, ctx: Arc<Context>) -> Re
     let coderuns: Api<CodeRun> = Api::namespaced(client.clone(), namespace);
     info!("✅ API clients created successfully");
 
+    // Paused runs are left untouched until un-paused (deletion still cleans up)
+    if code_run.spec.paused && code_run.metadata.deletion_timestamp.is_none() {
+        info!("CodeRun {} is paused, skipping reconciliation", name);
+        return Ok(Action::await_change());
+    }
+
     // Handle finalizers for cleanup
     let result = finalizer(
         &coderuns,
@@ -97,6 +108,11 @@ async fn reconcile_code_create_or_update(code_run: Arc<CodeRun>, ctx: &Context)
                 info!("Already failed, no retry logic");
                 return Ok(Action::await_change());
             }
+            "Cancelled" => {
+                // Set by the MCP cancel tool, which also deletes the Job; don't start another
+                info!("Cancelled, not starting a new job");
+                return Ok(Action::await_change());
+            }
             "Running" => {
                 info!("Status shows running, checking actual job state");
                 // Continue to job state check below
@@ -114,14 +130,104 @@ async fn reconcile_code_create_or_update(code_run: Arc<CodeRun>, ctx: &Context)
     let jobs: Api<Job> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
     let configmaps: Api<ConfigMap> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
     let pvcs: Api<PersistentVolumeClaim> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
-    let job_name = generate_code_job_name(&code_run);
+    let job_name = resolve_job_name(&jobs, &code_job_key(&code_run), &ctx.namespace).await?;
     info!("Generated job name: {}", job_name);
 
-    let job_state = check_code_job_state(&jobs, &job_name).await?;
+    let job_state = check_code_job_state(&jobs, &job_name, &ctx.namespace).await?;
     info!("Current job state: {:?}", job_state);
 
     match job_state {
         CodeJobState::NotFound => {
+            // Coalesce rapid spec edits into a single Job at the final version
+            if let Some(settle_seconds) = ctx.config.job.spec_settle_seconds.filter(|s| *s > 0) {
+                let settle = std::time::Duration::from_secs(settle_seconds);
+                match spec_settle_state(&code_run, settle, chrono::Utc::now()) {
+                    SpecSettle::NewGeneration => {
+                        info!(
+                            "Spec changed, waiting {}s for edits to settle",
+                            settle_seconds
+                        );
+                        record_observed_generation(&code_run, ctx).await?;
+                        return Ok(Action::requeue(settle));
+                    }
+                    SpecSettle::Waiting(remaining) => {
+                        info!("Spec still settling, {}s left", remaining.as_secs());
+                        return Ok(Action::requeue(remaining));
+                    }
+                    SpecSettle::Settled => {}
+                }
+            }
+
+            // Reject partially-specified repositories before creating anything
+            if let Err(message) = validate_code_run(&code_run.spec, &ctx.config.permissions) {
+                info!("CodeRun spec is invalid: {}", message);
+                fail_code_run_with_reason(&code_run, ctx, "InvalidSpec", &message).await?;
+                return Ok(Action::await_change());
+            }
+
+            // Don't start a new Job on a workspace held for inspection
+            let pvc_name = format!("workspace-{}", code_run.spec.service);
+            let mut hold = workspace_hold(&pvcs, &pvc_name, &ctx.namespace).await?;
+            if let Some(preserved_for) = hold.as_deref() {
+                let coderuns: Api<CodeRun> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
+                let preserved_run = coderuns.get_opt(preserved_for).await.for_resource(
+                    "get",
+                    "CodeRun",
+                    preserved_for,
+                    &ctx.namespace,
+                )?;
+                if workspace_hold_is_stale(&code_run, preserved_run.as_ref()) {
+                    info!(
+                        "Releasing workspace PVC {} held for CodeRun {}, the hold is stale",
+                        pvc_name, preserved_for
+                    );
+                    release_code_workspace(&pvcs, &pvc_name, &ctx.namespace).await?;
+                    hold = None;
+                }
+            }
+            if let Some(preserved_for) = hold {
+                info!(
+                    "Workspace PVC {} is preserved for CodeRun {}, waiting before starting a job",
+                    pvc_name, preserved_for
+                );
+                update_code_status_with_completion(
+                    &code_run,
+                    ctx,
+                    "Pending",
+                    &format!(
+                        "Workspace PVC {pvc_name} is preserved for inspection of failed CodeRun {preserved_for}; delete that CodeRun or remove the {PRESERVED_WORKSPACE_ANNOTATION} annotation to reuse it"
+                    ),
+                    false,
+                )
+                .await?;
+                return Ok(Action::requeue(std::time::Duration::from_secs(60)));
+            }
+
+            // Enforce the retry budget before starting another Job
+            let total_attempts = code_run
+                .status
+                .as_ref()
+                .and_then(|s| s.total_attempts)
+                .unwrap_or(0);
+            if let Some(max_attempts) = ctx.config.job.max_total_attempts {
+                if total_attempts >= max_attempts {
+                    info!(
+                        "CodeRun has used {} of {} allowed attempts, refusing to start a new job",
+                        total_attempts, max_attempts
+                    );
+                    fail_code_run_with_reason(
+                        &code_run,
+                        ctx,
+                        "MaxAttemptsExceeded",
+                        &format!(
+                            "Retry budget exhausted after {total_attempts} attempts (max {max_attempts})"
+                        ),
+                    )
+                    .await?;
+                    return Ok(Action::await_change());
+                }
+            }
+
             info!("No existing job found, using optimistic job creation");
 
             // STEP 3: Optimistic job creation with conflict handling (copied from working docs controller)
@@ -134,6 +240,8 @@ async fn reconcile_code_create_or_update(code_run: Arc<CodeRun>, ctx: &Context)
                 .reconcile_create_or_update(&code_run)
                 .await?;
 
+            record_code_attempt(&code_run, ctx, total_attempts + 1).await?;
+
             // Update status to Running (same pattern as docs)
             update_code_status_with_completion(
                 &code_run,
@@ -151,6 +259,65 @@ async fn reconcile_code_create_or_update(code_run: Arc<CodeRun>, ctx: &Context)
         CodeJobState::Running => {
             info!("Job is still running, monitoring progress");
 
+            // A restart between creating the Job and recording it leaves status unaware of it
+            let recorded_job = code_run.status.as_ref().and_then(|s| s.job_name.as_deref());
+            if job_needs_adoption(recorded_job, &job_name) {
+                if let Some(job) = jobs.get_opt(&job_name).await.for_resource(
+                    "get",
+                    "Job",
+                    &job_name,
+                    &ctx.namespace,
+                )? {
+                    let ctx_arc = Arc::new(ctx.clone());
+                    let resource_manager =
+                        CodeResourceManager::new(&jobs, &configmaps, &pvcs, &ctx.config, &ctx_arc);
+                    resource_manager.adopt_job(&code_run, &job).await?;
+                    let total_attempts = code_run
+                        .status
+                        .as_ref()
+                        .and_then(|s| s.total_attempts)
+                        .unwrap_or(0);
+                    record_code_attempt(&code_run, ctx, total_attempts + 1).await?;
+                }
+            }
+
+            // Fail fast instead of waiting for activeDeadlineSeconds on a pod that can't start
+            if let Some(timeout) = ctx.config.job.startup_timeout_seconds {
+                let pods: Api<Pod> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
+                let job_pods = pods
+                    .list(&ListParams::default().labels(&format!("job-name={job_name}")))
+                    .await?;
+                let now = chrono::Utc::now();
+                let startup_failure = job_pods
+                    .iter()
+                    .find_map(|pod| detect_startup_failure(pod, now, timeout));
+                if let Some((reason, message)) = startup_failure {
+                    info!("Job {} failed to start: {} ({})", job_name, reason, message);
+                    jobs.delete(
+                        &job_name,
+                        &DeleteParams {
+                            propagation_policy: Some(PropagationPolicy::Background),
+                            ..Default::default()
+                        },
+                    )
+                    .await
+                    .for_resource(
+                        "delete",
+                        "Job",
+                        &job_name,
+                        &ctx.namespace,
+                    )?;
+                    fail_code_run_with_reason(
+                        &code_run,
+                        ctx,
+                        &reason,
+                        &format!("Job pod did not start within {timeout}s: {message}"),
+                    )
+                    .await?;
+                    return Ok(Action::await_change());
+                }
+            }
+
             // Update status to Running with workCompleted=false
             update_code_status_with_completion(
                 &code_run,
@@ -161,39 +328,92 @@ async fn reconcile_code_create_or_update(code_run: Arc<CodeRun>, ctx: &Context)
             )
             .await?;
 
+            let mut requeue_seconds = 30;
+            if let Some(interval) = ctx.config.job.progress_interval_seconds {
+                report_code_progress(&code_run, ctx, &job_name, interval).await;
+                requeue_seconds = requeue_seconds.min(interval.max(5));
+            }
+
             // Continue monitoring
-            Ok(Action::requeue(std::time::Duration::from_secs(30)))
+            Ok(Action::requeue(std::time::Duration::from_secs(
+                requeue_seconds,
+            )))
         }
 
         CodeJobState::Completed => {
             info!("Job completed successfully - marking work as completed");
 
+            // The exit code only says the agent finished; the test check says how well
+            let result_detail = latest_job_pod_logs(ctx, &job_name, RESULT_DETAIL_LOG_LINES)
+                .await
+                .and_then(|logs| result_detail_from_logs(&logs));
+            let message = match result_detail {
+                Some("TestsPassed") => {
+                    "Code implementation completed successfully with passing tests"
+                }
+                Some("TestsFailed") => "Code implementation completed, but tests failed",
+                Some("NoTests") => "Code implementation completed successfully (no tests found)",
+                _ => "Code implementation completed successfully",
+            };
+            if let Some(detail) = result_detail {
+                info!("Test check reported {}", detail);
+                record_result_detail(&code_run, ctx, detail).await?;
+            }
+
             // CRITICAL: Update with work_completed=true for TTL safety
-            update_code_status_with_completion(
-                &code_run,
-                ctx,
-                "Succeeded",
-                "Code implementation completed successfully",
-                true,
-            )
-            .await?;
+            update_code_status_with_completion(&code_run, ctx, "Succeeded", message, true).await?;
 
             // Use await_change() to stop reconciliation
             Ok(Action::await_change())
         }
 
         CodeJobState::Failed => {
+            // A preempted agent didn't fail on its own; rerun it instead of failing the task
+            let job_pods = Api::<Pod>::namespaced(ctx.client.clone(), &ctx.namespace)
+                .list(&ListParams::default().labels(&format!("job-name={job_name}")))
+                .await?;
+            let preemption = match jobs.get_opt(&job_name).await.for_resource(
+                "get",
+                "Job",
+                &job_name,
+                &ctx.namespace,
+            )? {
+                Some(job) => detect_preemption(&job, &job_pods.items),
+                None => None,
+            };
+            if let Some(message) = preemption {
+                info!("Job {} was preempted: {}", job_name, message);
+                jobs.delete(
+                    &job_name,
+                    &DeleteParams {
+                        propagation_policy: Some(PropagationPolicy::Background),
+                        ..Default::default()
+                    },
+                )
+                .await
+                .for_resource("delete", "Job", &job_name, &ctx.namespace)?;
+                requeue_preempted_code_run(&code_run, ctx, &message).await?;
+                return Ok(Action::requeue(std::time::Duration::from_secs(10)));
+            }
+
             info!("Job failed - marking as failed");
 
+            // The Job's TTL removes its pods within seconds, so keep the log tail for a retry now
+            if let Some(logs) =
+                latest_job_pod_logs(ctx, &job_name, PREVIOUS_FAILURE_LOG_LINES).await
+            {
+                record_failure_log_tail(&code_run, ctx, failure_log_tail(&logs)).await?;
+            }
+
+            let message = if code_run.spec.keep_workspace {
+                let pvc_name = preserve_code_workspace(&code_run, ctx, &pvcs).await?;
+                format!("Code implementation failed; workspace preserved in PVC {pvc_name}")
+            } else {
+                "Code implementation failed".to_string()
+            };
+
             // Update to failed status (no work_completed=true for failures)
-            update_code_status_with_completion(
-                &code_run,
-                ctx,
-                "Failed",
-                "Code implementation failed",
-                false,
-            )
-            .await?;
+            update_code_status_with_completion(&code_run, ctx, "Failed", &message, false).await?;
 
             // Use await_change() to stop reconciliation
             Ok(Action::await_change())
@@ -210,6 +430,13 @@ async fn cleanup_code_resources(code_run: Arc<CodeRun>, ctx: &Context) -> Result
     let configmaps: Api<ConfigMap> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
     let pvcs: Api<PersistentVolumeClaim> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
 
+    // A deleted run no longer needs its workspace held for inspection
+    let pvc_name = format!("workspace-{}", code_run.spec.service);
+    if workspace_hold(&pvcs, &pvc_name, &ctx.namespace).await? == Some(code_run.name_any()) {
+        info!("Releasing workspace PVC {} held for this CodeRun", pvc_name);
+        release_code_workspace(&pvcs, &pvc_name, &ctx.namespace).await?;
+    }
+
     // Create resource manager and delegate
     let ctx_arc = Arc::new(ctx.clone());
     let resource_manager =
@@ -227,7 +454,562 @@ pub enum CodeJobState {
     Failed,
 }
 
-fn generate_code_job_name(code_run: &CodeRun) -> String {
+/// Pod waiting reasons that mean the agent image will not start without intervention
+const IMAGE_PULL_FAILURES: &[&str] = &["ImagePullBackOff", "ErrImagePull", "InvalidImageName"];
+
+/// Detect a pod that has been unschedulable or unable to pull its image for longer than `timeout_seconds`
+fn detect_startup_failure(
+    pod: &Pod,
+    now: chrono::DateTime<chrono::Utc>,
+    timeout_seconds: u64,
+) -> Option<(String, String)> {
+    let status = pod.status.as_ref()?;
+    let timeout = chrono::Duration::seconds(timeout_seconds as i64);
+    let exceeded = |since: Option<&chrono::DateTime<chrono::Utc>>| {
+        since.is_some_and(|since| now - *since > timeout)
+    };
+
+    let unschedulable = status.conditions.iter().flatten().find(|c| {
+        c.type_ == "PodScheduled"
+            && c.status == "False"
+            && c.reason.as_deref() == Some("Unschedulable")
+    });
+    if let Some(condition) = unschedulable {
+        if exceeded(condition.last_transition_time.as_ref().map(|t| &t.0)) {
+            return Some((
+                "Unschedulable".to_string(),
+                condition.message.clone().unwrap_or_default(),
+            ));
+        }
+    }
+
+    let created = pod.metadata.creation_timestamp.as_ref().map(|t| &t.0);
+    let container_statuses = status
+        .init_container_statuses
+        .iter()
+        .flatten()
+        .chain(status.container_statuses.iter().flatten());
+    for container in container_statuses {
+        let Some(waiting) = container.state.as_ref().and_then(|s| s.waiting.as_ref()) else {
+            continue;
+        };
+        let reason = waiting.reason.as_deref().unwrap_or("");
+        if IMAGE_PULL_FAILURES.contains(&reason) && exceeded(created) {
+            return Some((
+                reason.to_string(),
+                format!(
+                    "container {}: {}",
+                    container.name,
+                    waiting.message.as_deref().unwrap_or(reason)
+                ),
+            ));
+        }
+    }
+
+    None
+}
+
+/// Detect a Job that failed because its pod was preempted, returning a status message
+///
+/// Prefers the pod's `DisruptionTarget` condition. Once the pod is gone, the Job's pod failure
+/// policy condition is the only record left, so any disruption is treated as preemption.
+fn detect_preemption(job: &Job, pods: &[Pod]) -> Option<String> {
+    let priority_note = |priority_class: Option<&String>| {
+        priority_class
+            .map(|class| format!(" (priorityClassName {class})"))
+            .unwrap_or_default()
+    };
+
+    for pod in pods {
+        let disruption = pod.status.as_ref().and_then(|status| {
+            status
+                .conditions
+                .iter()
+                .flatten()
+                .find(|c| c.type_ == "DisruptionTarget" && c.status == "True")
+        });
+        if let Some(condition) = disruption {
+            if condition.reason.as_deref() != Some("PreemptionByScheduler") {
+                return None;
+            }
+            return Some(format!(
+                "Pod {} was preempted by a higher-priority workload{}: {}",
+                pod.name_any(),
+                priority_note(
+                    pod.spec
+                        .as_ref()
+                        .and_then(|s| s.priority_class_name.as_ref())
+                ),
+                condition
+                    .message
+                    .as_deref()
+                    .unwrap_or("PreemptionByScheduler")
+            ));
+        }
+    }
+    if !pods.is_empty() {
+        return None;
+    }
+
+    let failed = job
+        .status
+        .as_ref()?
+        .conditions
+        .iter()
+        .flatten()
+        .find(|c| c.type_ == "Failed" && c.status == "True")?;
+    let message = failed.message.as_deref().unwrap_or("");
+    (failed.reason.as_deref() == Some("PodFailurePolicy") && message.contains("DisruptionTarget"))
+        .then(|| {
+            let priority_class = job
+                .spec
+                .as_ref()
+                .and_then(|s| s.template.spec.as_ref())
+                .and_then(|s| s.priority_class_name.as_ref());
+            format!(
+                "Job pod was preempted{}: {message}",
+                priority_note(priority_class)
+            )
+        })
+}
+
+/// Log lines scanned for the agent's latest progress
+const PROGRESS_LOG_LINES: i64 = 50;
+
+/// Longest progress line copied into the status message
+const PROGRESS_MAX_CHARS: usize = 200;
+
+/// Copy the agent's latest output line into `status.message`, at most once per `interval_seconds`
+///
+/// Best effort: failures are logged and never fail the reconcile.
+async fn report_code_progress(
+    code_run: &CodeRun,
+    ctx: &Context,
+    job_name: &str,
+    interval_seconds: u64,
+) {
+    let status = code_run.status.as_ref();
+    let last_update = status
+        .and_then(|s| s.last_update.as_deref())
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok());
+    if last_update.is_some_and(|t| {
+        chrono::Utc::now().signed_duration_since(t)
+            < chrono::Duration::seconds(interval_seconds as i64)
+    }) {
+        return;
+    }
+
+    let Some(logs) = latest_job_pod_logs(ctx, job_name, PROGRESS_LOG_LINES).await else {
+        return;
+    };
+    let Some(line) = latest_progress_line(&logs) else {
+        return;
+    };
+
+    let message = format!("Code task in progress: {line}");
+    if status.is_some_and(|s| s.message.as_deref() == Some(message.as_str())) {
+        return;
+    }
+
+    let coderuns: Api<CodeRun> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
+    let status_patch = json!({
+        "status": {
+            "message": message,
+            "lastUpdate": chrono::Utc::now().to_rfc3339(),
+        }
+    });
+    if let Err(e) = coderuns
+        .patch_status(
+            &code_run.name_any(),
+            &PatchParams::default(),
+            &Patch::Merge(&status_patch),
+        )
+        .await
+    {
+        warn!("Could not record progress for CodeRun: {}", e);
+    }
+}
+
+/// Tail of the agent container's log in the newest pod of `job_name`
+///
+/// Best effort: returns `None` (after logging) when the pod or its logs can't be read.
+pub(super) async fn latest_job_pod_logs(
+    ctx: &Context,
+    job_name: &str,
+    tail_lines: i64,
+) -> Option<String> {
+    let pods: Api<Pod> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
+    let pod_name = match pods
+        .list(&ListParams::default().labels(&format!("job-name={job_name}")))
+        .await
+    {
+        Ok(list) => list
+            .items
+            .into_iter()
+            .max_by(|a, b| {
+                a.metadata
+                    .creation_timestamp
+                    .cmp(&b.metadata.creation_timestamp)
+            })
+            .map(|pod| pod.name_any())?,
+        Err(e) => {
+            warn!("Could not list pods of job {}: {}", job_name, e);
+            return None;
+        }
+    };
+
+    let log_params = LogParams {
+        container: Some("claude-code".to_string()),
+        tail_lines: Some(tail_lines),
+        ..Default::default()
+    };
+    match pods.logs(&pod_name, &log_params).await {
+        Ok(logs) => Some(logs),
+        Err(e) => {
+            warn!("Could not read logs of pod {}: {}", pod_name, e);
+            None
+        }
+    }
+}
+
+/// Prefix of the line container.sh prints with the outcome of the post-run test check
+const RESULT_DETAIL_MARKER: &str = "CODERUN_RESULT_DETAIL=";
+
+/// Outcomes the test check reports, recorded as `status.resultDetail`
+const RESULT_DETAILS: &[&str] = &["TestsPassed", "TestsFailed", "NoTests"];
+
+/// Log lines scanned for the test check's result marker
+const RESULT_DETAIL_LOG_LINES: i64 = 50;
+
+/// The test outcome the agent's container reported, if it got that far
+fn result_detail_from_logs(logs: &str) -> Option<&'static str> {
+    let reported = logs
+        .lines()
+        .rev()
+        .find_map(|line| line.trim().strip_prefix(RESULT_DETAIL_MARKER))?;
+    RESULT_DETAILS
+        .iter()
+        .copied()
+        .find(|detail| *detail == reported.trim())
+}
+
+/// Record the end of a failed Job's log in `status.failureLogTail`
+async fn record_failure_log_tail(code_run: &CodeRun, ctx: &Context, tail: &str) -> Result<()> {
+    let coderuns: Api<CodeRun> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
+    let status_patch = json!({
+        "status": {
+            "failureLogTail": tail,
+        }
+    });
+    coderuns
+        .patch_status(
+            &code_run.name_any(),
+            &PatchParams::default(),
+            &Patch::Merge(&status_patch),
+        )
+        .await
+        .for_resource(
+            "update status of",
+            "CodeRun",
+            &code_run.name_any(),
+            &ctx.namespace,
+        )?;
+    Ok(())
+}
+
+/// Record the test outcome of a finished Job in `status.resultDetail`
+async fn record_result_detail(code_run: &CodeRun, ctx: &Context, detail: &str) -> Result<()> {
+    let coderuns: Api<CodeRun> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
+    let status_patch = json!({
+        "status": {
+            "resultDetail": detail,
+        }
+    });
+    coderuns
+        .patch_status(
+            &code_run.name_any(),
+            &PatchParams::default(),
+            &Patch::Merge(&status_patch),
+        )
+        .await
+        .for_resource(
+            "update status of",
+            "CodeRun",
+            &code_run.name_any(),
+            &ctx.namespace,
+        )?;
+    Ok(())
+}
+
+/// Pick the most recent meaningful line from the agent container's output
+///
+/// Claude runs with `--output-format stream-json`, so assistant events are summarised as
+/// their text or the tool being used; plain script output is taken as-is, skipping banners.
+fn latest_progress_line(logs: &str) -> Option<String> {
+    logs.lines().rev().find_map(|line| {
+        let line = line.trim();
+        let summary = if line.starts_with('{') {
+            let event: serde_json::Value = serde_json::from_str(line).ok()?;
+            if event["type"] != "assistant" {
+                return None;
+            }
+            let content = event["message"]["content"].as_array()?.last()?;
+            match content["type"].as_str()? {
+                "text" => content["text"]
+                    .as_str()?
+                    .lines()
+                    .map(str::trim)
+                    .find(|l| !l.is_empty())?
+                    .to_string(),
+                "tool_use" => {
+                    let name = content["name"].as_str()?;
+                    let input = &content["input"];
+                    match ["description", "command", "file_path", "pattern"]
+                        .iter()
+                        .find_map(|key| input[key].as_str())
+                    {
+                        Some(detail) => format!("{name}: {detail}"),
+                        None => name.to_string(),
+                    }
+                }
+                _ => return None,
+            }
+        } else if line.chars().any(char::is_alphanumeric) {
+            line.to_string()
+        } else {
+            return None;
+        };
+
+        let summary = summary.split_whitespace().collect::<Vec<_>>().join(" ");
+        Some(match summary.char_indices().nth(PROGRESS_MAX_CHARS) {
+            Some((end, _)) => format!("{}…", &summary[..end]),
+            None => summary,
+        })
+    })
+}
+
+/// Every spec check a `CodeRun` must pass before its Job starts (also run by the admission webhook)
+pub fn validate_code_run(
+    spec: &CodeRunSpec,
+    permissions: &PermissionsConfig,
+) -> std::result::Result<(), String> {
+    validate_code_run_spec(spec).and_then(|()| validate_tools_profile(spec, permissions))
+}
+
+/// Check that `toolsProfile` names one of the configured permission presets
+fn validate_tools_profile(
+    spec: &CodeRunSpec,
+    permissions: &PermissionsConfig,
+) -> std::result::Result<(), String> {
+    match spec.selected_tools_profile() {
+        Some(name) if !permissions.profiles.contains_key(name) => {
+            let available = permissions
+                .profiles
+                .keys()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", ");
+            Err(format!(
+                "toolsProfile '{name}' is not defined (available: {})",
+                if available.is_empty() {
+                    "none"
+                } else {
+                    &available
+                }
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Check that the repository and GitHub fields needed to start a Job are all present
+fn validate_code_run_spec(spec: &CodeRunSpec) -> std::result::Result<(), String> {
+    let github_app = spec.github_app.as_deref().unwrap_or("");
+    let required = [
+        ("repositoryUrl", spec.repository_url.as_str()),
+        ("docsRepositoryUrl", spec.docs_repository_url.as_str()),
+        ("docsBranch", spec.docs_branch.as_str()),
+        ("githubApp", github_app),
+    ];
+
+    let mut problems: Vec<String> = required
+        .iter()
+        .filter(|(_, value)| value.trim().is_empty())
+        .map(|(field, _)| format!("spec.{field} must not be empty"))
+        .collect();
+
+    let mut invalid_keys: Vec<&str> = spec
+        .git_config
+        .keys()
+        .map(String::as_str)
+        .filter(|key| !is_valid_git_config_key(key))
+        .collect();
+    invalid_keys.sort_unstable();
+    problems.extend(
+        invalid_keys
+            .into_iter()
+            .map(|key| format!("spec.gitConfig key '{key}' must look like section.key")),
+    );
+
+    if spec
+        .base_branch
+        .as_deref()
+        .is_some_and(|branch| branch.trim().is_empty())
+    {
+        problems.push("spec.baseBranch must not be empty when set".to_string());
+    }
+
+    if let Some(visibility) = spec.repository_visibility_hint() {
+        if !matches!(visibility, "public" | "private") {
+            problems.push(format!(
+                "spec.repositoryVisibility '{visibility}' must be 'public' or 'private'"
+            ));
+        }
+    }
+
+    if let Some(sha) = spec.pinned_commit() {
+        if !(7..=40).contains(&sha.len()) || !sha.chars().all(|c| c.is_ascii_hexdigit()) {
+            problems.push(format!(
+                "spec.commitSha '{sha}' must be a 7-40 character hex commit SHA"
+            ));
+        }
+    }
+
+    // Any fixed timestamp works here; only the template's shape is being checked
+    if let Err(e) = spec.render_feature_branch("19700101000000", None) {
+        problems.push(format!("spec.workingBranch: {e}"));
+    }
+
+    let mut seen_names = std::collections::HashSet::new();
+    for extra in &spec.extra_volumes {
+        let name = extra.name.as_str();
+        if RESERVED_VOLUME_NAMES.contains(&name) || !seen_names.insert(name) {
+            problems.push(format!(
+                "spec.extraVolumes name '{name}' is reserved or duplicated"
+            ));
+        }
+        if !extra.mount_path.starts_with('/') {
+            problems.push(format!(
+                "spec.extraVolumes mountPath '{}' must be absolute",
+                extra.mount_path
+            ));
+        } else if paths_overlap(&extra.mount_path, "/workspace") {
+            problems.push(format!(
+                "spec.extraVolumes mountPath '{}' must not overlap /workspace",
+                extra.mount_path
+            ));
+        }
+    }
+
+    problems.extend(sidecar_problems(spec));
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems.join("; "))
+    }
+}
+
+/// Container names already used by the code Job
+const RESERVED_CONTAINER_NAMES: &[&str] = &["claude-code", "docker-daemon"];
+
+/// Port the Docker daemon listens on when `enableDocker` is set (TLS disabled)
+const DOCKER_DAEMON_PORT: u16 = 2375;
+
+fn sidecar_problems(spec: &CodeRunSpec) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut seen_names = std::collections::HashSet::new();
+    let mut seen_ports = std::collections::HashSet::new();
+
+    for sidecar in &spec.sidecars {
+        let name = sidecar.name.as_str();
+        // Container names must be DNS-1123 labels
+        let valid_name = !name.is_empty()
+            && name.len() <= 63
+            && name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+            && !name.starts_with('-')
+            && !name.ends_with('-');
+        if !valid_name {
+            problems.push(format!(
+                "spec.sidecars name '{name}' must be a lowercase DNS label"
+            ));
+        } else if RESERVED_CONTAINER_NAMES.contains(&name) || !seen_names.insert(name) {
+            problems.push(format!(
+                "spec.sidecars name '{name}' is reserved or duplicated"
+            ));
+        }
+
+        if sidecar.image.trim().is_empty() {
+            problems.push(format!("spec.sidecars '{name}' image must not be empty"));
+        }
+
+        // Containers in a pod share one network namespace, so ports can't repeat
+        for &port in &sidecar.ports {
+            if port == DOCKER_DAEMON_PORT && spec.enable_docker.unwrap_or(false) {
+                problems.push(format!(
+                    "spec.sidecars '{name}' port {port} is used by the Docker daemon (enableDocker)"
+                ));
+            } else if port == 0 || !seen_ports.insert(port) {
+                problems.push(format!(
+                    "spec.sidecars '{name}' port {port} is invalid or already used by another sidecar"
+                ));
+            }
+        }
+
+        for volume in &sidecar.shared_volumes {
+            let known = volume == "workspace"
+                || spec.extra_volumes.iter().any(|extra| &extra.name == volume);
+            if !known {
+                problems.push(format!(
+                    "spec.sidecars '{name}' sharedVolumes entry '{volume}' must be 'workspace' or an extraVolumes name"
+                ));
+            }
+        }
+    }
+
+    problems
+}
+
+/// Volume names already used by the code Job
+const RESERVED_VOLUME_NAMES: &[&str] = &[
+    "task-files",
+    "agents-config",
+    "workspace",
+    "docker-sock-dir",
+    "docker-data",
+    CaBundleConfig::VOLUME_NAME,
+];
+
+/// True if one path is the other or contains it
+fn paths_overlap(a: &str, b: &str) -> bool {
+    let a = a.trim_end_matches('/');
+    let b = b.trim_end_matches('/');
+    let contains =
+        |outer: &str, inner: &str| inner == outer || inner.starts_with(&format!("{outer}/"));
+    contains(a, b) || contains(b, a)
+}
+
+/// Git config keys are `section[.subsection].name`; only the subsection may contain dots
+fn is_valid_git_config_key(key: &str) -> bool {
+    let is_name = |part: &str| {
+        !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    };
+    match (key.split_once('.'), key.rsplit_once('.')) {
+        (Some((section, _)), Some((_, name))) => {
+            is_name(section) && is_name(name) && !key.chars().any(char::is_control)
+        }
+        _ => false,
+    }
+}
+
+pub(super) fn generate_code_job_name(code_run: &CodeRun) -> String {
+    resource_name(&code_job_key(code_run))
+}
+
+/// Job name for `code_run` before it is made a valid, length-capped resource name
+fn code_job_key(code_run: &CodeRun) -> String {
     let namespace = code_run.metadata.namespace.as_deref().unwrap_or("default");
     let name = code_run.metadata.name.as_deref().unwrap_or("unknown");
     let uid_suffix = code_run
@@ -240,11 +1022,13 @@ fn generate_code_job_name(code_run: &CodeRun) -> String {
     let context_version = code_run.spec.context_version;
 
     format!("code-{namespace}-{name}-{uid_suffix}-t{task_id}-v{context_version}")
-        .replace(['_', '.'], "-")
-        .to_lowercase()
 }
 
-async fn check_code_job_state(jobs: &Api<Job>, job_name: &str) -> Result<CodeJobState> {
+async fn check_code_job_state(
+    jobs: &Api<Job>,
+    job_name: &str,
+    namespace: &str,
+) -> Result<CodeJobState> {
     match jobs.get(job_name).await {
         Ok(job) => {
             if let Some(status) = &job.status {
@@ -254,7 +1038,7 @@ async fn check_code_job_state(jobs: &Api<Job>, job_name: &str) -> Result<CodeJob
             }
         }
         Err(kube::Error::Api(response)) if response.code == 404 => Ok(CodeJobState::NotFound),
-        Err(e) => Err(e.into()),
+        Err(e) => Err(e).for_resource("get", "Job", job_name, namespace),
     }
 }
 
@@ -337,7 +1121,13 @@ async fn update_code_status_with_completion(
             &PatchParams::default(),
             &Patch::Merge(&status_patch),
         )
-        .await?;
+        .await
+        .for_resource(
+            "update status of",
+            "CodeRun",
+            &code_run.name_any(),
+            &ctx.namespace,
+        )?;
 
     info!(
         "Status updated successfully to '{}' with work_completed={}",
@@ -345,3 +1135,1104 @@ async fn update_code_status_with_completion(
     );
     Ok(())
 }
+
+/// Where a CodeRun's current spec generation is in the `specSettleSeconds` window
+#[derive(Debug, PartialEq, Eq)]
+enum SpecSettle {
+    /// Generation not seen before: record it and wait the full window
+    NewGeneration,
+    /// Generation recorded; wait out the rest of the window
+    Waiting(std::time::Duration),
+    Settled,
+}
+
+fn spec_settle_state(
+    code_run: &CodeRun,
+    settle: std::time::Duration,
+    now: chrono::DateTime<chrono::Utc>,
+) -> SpecSettle {
+    let generation = code_run.metadata.generation.unwrap_or(0);
+    let status = code_run.status.as_ref();
+    if status.and_then(|s| s.observed_generation) != Some(generation) {
+        return SpecSettle::NewGeneration;
+    }
+
+    let Some(observed_at) = status
+        .and_then(|s| s.generation_observed_at.as_deref())
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+    else {
+        return SpecSettle::Settled;
+    };
+    let elapsed = (now - observed_at.with_timezone(&chrono::Utc))
+        .to_std()
+        .unwrap_or_default();
+    match settle.checked_sub(elapsed) {
+        Some(remaining) if !remaining.is_zero() => SpecSettle::Waiting(remaining),
+        _ => SpecSettle::Settled,
+    }
+}
+
+/// Start the settle window for the CodeRun's current spec generation
+async fn record_observed_generation(code_run: &CodeRun, ctx: &Context) -> Result<()> {
+    let coderuns: Api<CodeRun> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let status_patch = json!({
+        "status": {
+            // phase is required, and a brand-new CodeRun has no status yet
+            "phase": code_run.status.as_ref().map_or("Pending", |s| s.phase.as_str()),
+            "observedGeneration": code_run.metadata.generation.unwrap_or(0),
+            "generationObservedAt": now,
+            "lastUpdate": now,
+        }
+    });
+
+    coderuns
+        .patch_status(
+            &code_run.name_any(),
+            &PatchParams::default(),
+            &Patch::Merge(&status_patch),
+        )
+        .await
+        .for_resource(
+            "update status of",
+            "CodeRun",
+            &code_run.name_any(),
+            &ctx.namespace,
+        )?;
+    Ok(())
+}
+
+/// Record the cumulative number of Jobs started for this CodeRun, the branch they work on
+/// and the tool deny rules the agent runs under
+async fn record_code_attempt(code_run: &CodeRun, ctx: &Context, total_attempts: u32) -> Result<()> {
+    let coderuns: Api<CodeRun> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
+
+    let status_patch = json!({
+        "status": {
+            "totalAttempts": total_attempts,
+            "gitBranch": code_run.feature_branch(ctx.config.defaults.working_branch.as_deref()),
+            "toolsDeny": CodeTemplateGenerator::tool_deny_rules(code_run, &ctx.config),
+            "lastUpdate": chrono::Utc::now().to_rfc3339(),
+        }
+    });
+
+    coderuns
+        .patch_status(
+            &code_run.name_any(),
+            &PatchParams::default(),
+            &Patch::Merge(&status_patch),
+        )
+        .await
+        .for_resource(
+            "update status of",
+            "CodeRun",
+            &code_run.name_any(),
+            &ctx.namespace,
+        )?;
+
+    info!("Recorded attempt {} for CodeRun", total_attempts);
+    Ok(())
+}
+
+/// Hold the workspace PVC of a failed CodeRun so later Jobs don't reuse it
+async fn preserve_code_workspace(
+    code_run: &CodeRun,
+    ctx: &Context,
+    pvcs: &Api<PersistentVolumeClaim>,
+) -> Result<String> {
+    let pvc_name = format!("workspace-{}", code_run.spec.service);
+    let name = code_run.name_any();
+
+    let pvc_patch = json!({
+        "metadata": {
+            "annotations": {
+                PRESERVED_WORKSPACE_ANNOTATION: name,
+            }
+        }
+    });
+    pvcs.patch(
+        &pvc_name,
+        &PatchParams::default(),
+        &Patch::Merge(&pvc_patch),
+    )
+    .await?;
+
+    let coderuns: Api<CodeRun> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
+    let status_patch = json!({
+        "status": {
+            "workspacePvc": pvc_name,
+            "lastUpdate": chrono::Utc::now().to_rfc3339(),
+        }
+    });
+    coderuns
+        .patch_status(&name, &PatchParams::default(), &Patch::Merge(&status_patch))
+        .await
+        .for_resource("update status of", "CodeRun", &name, &ctx.namespace)?;
+
+    info!(
+        "Preserved workspace PVC {} for failed CodeRun {}",
+        pvc_name, name
+    );
+    Ok(pvc_name)
+}
+
+/// The failed CodeRun a workspace PVC is held for, if any
+async fn workspace_hold(
+    pvcs: &Api<PersistentVolumeClaim>,
+    pvc_name: &str,
+    namespace: &str,
+) -> Result<Option<String>> {
+    Ok(pvcs
+        .get_opt(pvc_name)
+        .await
+        .for_resource("get", "PersistentVolumeClaim", pvc_name, namespace)?
+        .and_then(|pvc| {
+            pvc.annotations()
+                .get(PRESERVED_WORKSPACE_ANNOTATION)
+                .cloned()
+        }))
+}
+
+/// Whether a workspace hold no longer applies to `code_run`: the run it was kept for is
+/// gone, or `code_run` is a newer run of the same task
+fn workspace_hold_is_stale(code_run: &CodeRun, preserved_run: Option<&CodeRun>) -> bool {
+    let Some(preserved_run) = preserved_run else {
+        return true;
+    };
+    preserved_run.uid() != code_run.uid()
+        && preserved_run.spec.task_id == code_run.spec.task_id
+        && preserved_run.creation_timestamp() < code_run.creation_timestamp()
+}
+
+/// Drop the inspection hold from a workspace PVC so new runs can use it
+async fn release_code_workspace(
+    pvcs: &Api<PersistentVolumeClaim>,
+    pvc_name: &str,
+    namespace: &str,
+) -> Result<()> {
+    let patch = json!({
+        "metadata": {
+            "annotations": {
+                PRESERVED_WORKSPACE_ANNOTATION: null,
+            }
+        }
+    });
+    pvcs.patch(pvc_name, &PatchParams::default(), &Patch::Merge(&patch))
+        .await
+        .for_resource("patch", "PersistentVolumeClaim", pvc_name, namespace)?;
+    Ok(())
+}
+
+/// Move the CodeRun to Failed with a specific condition reason
+async fn fail_code_run_with_reason(
+    code_run: &CodeRun,
+    ctx: &Context,
+    reason: &str,
+    message: &str,
+) -> Result<()> {
+    let coderuns: Api<CodeRun> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let condition = CodeRunCondition {
+        condition_type: "Failed".to_string(),
+        status: "True".to_string(),
+        last_transition_time: Some(now.clone()),
+        reason: Some(reason.to_string()),
+        message: Some(message.to_string()),
+    };
+
+    let status_patch = json!({
+        "status": {
+            "phase": "Failed",
+            "message": message,
+            "lastUpdate": now,
+            "workCompleted": false,
+            "conditions": [condition],
+        }
+    });
+
+    coderuns
+        .patch_status(
+            &code_run.name_any(),
+            &PatchParams::default(),
+            &Patch::Merge(&status_patch),
+        )
+        .await
+        .for_resource(
+            "update status of",
+            "CodeRun",
+            &code_run.name_any(),
+            &ctx.namespace,
+        )?;
+
+    info!("CodeRun marked Failed with reason {}", reason);
+    Ok(())
+}
+
+/// Move a preempted CodeRun back to Pending so the next reconcile starts a fresh Job
+async fn requeue_preempted_code_run(
+    code_run: &CodeRun,
+    ctx: &Context,
+    message: &str,
+) -> Result<()> {
+    let coderuns: Api<CodeRun> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
+    let now = chrono::Utc::now().to_rfc3339();
+    let message = format!("{message}; requeuing");
+
+    let condition = CodeRunCondition {
+        condition_type: "Pending".to_string(),
+        status: "True".to_string(),
+        last_transition_time: Some(now.clone()),
+        reason: Some("Preempted".to_string()),
+        message: Some(message.clone()),
+    };
+
+    let status_patch = json!({
+        "status": {
+            "phase": "Pending",
+            "message": message,
+            "lastUpdate": now,
+            "workCompleted": false,
+            "conditions": [condition],
+        }
+    });
+
+    coderuns
+        .patch_status(
+            &code_run.name_any(),
+            &PatchParams::default(),
+            &Patch::Merge(&status_patch),
+        )
+        .await
+        .for_resource(
+            "update status of",
+            "CodeRun",
+            &code_run.name_any(),
+            &ctx.namespace,
+        )?;
+
+    info!("CodeRun requeued after preemption");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::config::ControllerConfig;
+    use crate::tasks::testing::FakeApiServer;
+
+    fn spec_with(field: &str, value: serde_json::Value) -> CodeRunSpec {
+        let mut spec = json!({
+            "taskId": 1,
+            "service": "api",
+            "repositoryUrl": "https://github.com/5dlabs/example",
+            "docsRepositoryUrl": "https://github.com/5dlabs/docs",
+            "model": "sonnet",
+            "githubApp": "5DLabs-Rex",
+            "docsBranch": "main"
+        });
+        spec[field] = value;
+        serde_json::from_value(spec).unwrap()
+    }
+
+    fn pod(status: serde_json::Value) -> Pod {
+        serde_json::from_value(json!({
+            "metadata": { "name": "job-pod", "creationTimestamp": "2025-01-01T00:00:00Z" },
+            "status": status
+        }))
+        .unwrap()
+    }
+
+    fn at(offset_seconds: i64) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::parse_from_rfc3339("2025-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc)
+            + chrono::Duration::seconds(offset_seconds)
+    }
+
+    #[test]
+    fn test_detect_startup_failure() {
+        let unschedulable = pod(json!({
+            "conditions": [{
+                "type": "PodScheduled",
+                "status": "False",
+                "reason": "Unschedulable",
+                "message": "0/3 nodes are available: insufficient memory",
+                "lastTransitionTime": "2025-01-01T00:00:00Z"
+            }]
+        }));
+        assert_eq!(detect_startup_failure(&unschedulable, at(60), 300), None);
+        assert_eq!(
+            detect_startup_failure(&unschedulable, at(301), 300),
+            Some((
+                "Unschedulable".to_string(),
+                "0/3 nodes are available: insufficient memory".to_string()
+            ))
+        );
+
+        let pull_failure = pod(json!({
+            "containerStatuses": [{
+                "name": "claude-code",
+                "image": "ghcr.io/5dlabs/claude:missing",
+                "imageID": "",
+                "ready": false,
+                "restartCount": 0,
+                "state": { "waiting": { "reason": "ImagePullBackOff", "message": "Back-off pulling image" } }
+            }]
+        }));
+        let (reason, message) = detect_startup_failure(&pull_failure, at(301), 300).unwrap();
+        assert_eq!(reason, "ImagePullBackOff");
+        assert_eq!(message, "container claude-code: Back-off pulling image");
+
+        let running = pod(json!({ "phase": "Running" }));
+        assert_eq!(detect_startup_failure(&running, at(3600), 300), None);
+    }
+
+    #[test]
+    fn test_detect_preemption() {
+        let failed_job = |reason: &str, message: &str| -> Job {
+            serde_json::from_value(json!({
+                "metadata": { "name": "code-job" },
+                "status": {
+                    "conditions": [{
+                        "type": "Failed", "status": "True", "reason": reason, "message": message
+                    }]
+                }
+            }))
+            .unwrap()
+        };
+        let disrupted = |reason: &str| {
+            pod(json!({
+                "phase": "Failed",
+                "conditions": [{
+                    "type": "DisruptionTarget", "status": "True", "reason": reason,
+                    "message": "Preempted in order to admit critical pod"
+                }]
+            }))
+        };
+        let policy_job = failed_job(
+            "PodFailurePolicy",
+            "Pod agent-platform/job-pod has condition DisruptionTarget matching FailJob rule at index 0",
+        );
+
+        let message =
+            detect_preemption(&policy_job, &[disrupted("PreemptionByScheduler")]).unwrap();
+        assert!(message.contains("job-pod was preempted"), "{message}");
+        assert!(detect_preemption(&policy_job, &[disrupted("EvictionByEvictionAPI")]).is_none());
+
+        // The preempted pod may already be gone; fall back to the Job's failure condition
+        assert!(detect_preemption(&policy_job, &[]).is_some());
+        let crashed = failed_job(
+            "BackoffLimitExceeded",
+            "Job has reached the specified backoff limit",
+        );
+        assert!(detect_preemption(&crashed, &[]).is_none());
+        assert!(detect_preemption(&crashed, &[pod(json!({ "phase": "Failed" }))]).is_none());
+    }
+
+    #[test]
+    fn test_latest_progress_line() {
+        let logs = [
+            "🚀 Cloning repository",
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"\nReading the task files"}]}}"#,
+            r#"{"type":"assistant","message":{"content":[{"type":"tool_use","name":"Bash","input":{"command":"cargo test"}}]}}"#,
+            r#"{"type":"user","message":{"content":[{"type":"tool_result","content":"ok"}]}}"#,
+            "════════════════",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(
+            latest_progress_line(&logs).as_deref(),
+            Some("Bash: cargo test")
+        );
+        assert_eq!(
+            latest_progress_line(&logs.replace("tool_use", "unknown")).as_deref(),
+            Some("Reading the task files")
+        );
+        assert_eq!(latest_progress_line("═══\n\n"), None);
+
+        let long = latest_progress_line(&"step ".repeat(100)).unwrap();
+        assert_eq!(long.chars().count(), PROGRESS_MAX_CHARS + 1);
+    }
+
+    #[test]
+    fn test_result_detail_from_logs() {
+        let logs = "CODERUN_RESULT_DETAIL=NoTests\ntest result: FAILED\nCODERUN_RESULT_DETAIL=TestsFailed\nexit 0\n";
+        assert_eq!(result_detail_from_logs(logs), Some("TestsFailed"));
+        assert_eq!(result_detail_from_logs("CODERUN_RESULT_DETAIL=Flaky"), None);
+        assert_eq!(
+            result_detail_from_logs("Claude has completed successfully."),
+            None
+        );
+    }
+
+    #[test]
+    fn test_complete_spec_is_valid() {
+        let spec = spec_with("service", json!("api"));
+        assert!(validate_code_run_spec(&spec).is_ok());
+    }
+
+    #[test]
+    fn test_missing_repository_fields_are_rejected() {
+        for field in [
+            "repositoryUrl",
+            "docsRepositoryUrl",
+            "docsBranch",
+            "githubApp",
+        ] {
+            let err = validate_code_run_spec(&spec_with(field, json!(" "))).unwrap_err();
+            assert_eq!(err, format!("spec.{field} must not be empty"));
+        }
+
+        let err = validate_code_run_spec(&spec_with("githubApp", json!(null))).unwrap_err();
+        assert_eq!(err, "spec.githubApp must not be empty");
+    }
+
+    #[test]
+    fn test_git_config_keys_are_validated() {
+        let valid = spec_with(
+            "gitConfig",
+            json!({
+                "http.postBuffer": "524288000",
+                "core.longpaths": "true",
+                "credential.https://github.example.com.helper": "store",
+            }),
+        );
+        assert!(validate_code_run_spec(&valid).is_ok());
+
+        let invalid = spec_with("gitConfig", json!({ "postBuffer": "1", "http.": "1" }));
+        assert_eq!(
+            validate_code_run_spec(&invalid).unwrap_err(),
+            "spec.gitConfig key 'http.' must look like section.key; \
+             spec.gitConfig key 'postBuffer' must look like section.key"
+        );
+    }
+
+    #[test]
+    fn test_tools_profile_must_be_configured() {
+        let mut permissions = ControllerConfig::default().permissions;
+        permissions
+            .profiles
+            .insert("readonly".to_string(), Default::default());
+
+        for profile in [json!(null), json!(""), json!("readonly")] {
+            let spec = spec_with("toolsProfile", profile);
+            assert!(validate_tools_profile(&spec, &permissions).is_ok());
+        }
+        assert_eq!(
+            validate_tools_profile(&spec_with("toolsProfile", json!("full")), &permissions)
+                .unwrap_err(),
+            "toolsProfile 'full' is not defined (available: readonly)"
+        );
+    }
+
+    #[test]
+    fn test_extra_volumes_must_not_overlap_workspace() {
+        let valid = spec_with(
+            "extraVolumes",
+            json!([
+                { "name": "cargo-cache", "mountPath": "/cache/cargo", "claimName": "cargo-cache" },
+                { "name": "scratch", "mountPath": "/scratch" },
+            ]),
+        );
+        assert!(validate_code_run_spec(&valid).is_ok());
+
+        let invalid = spec_with(
+            "extraVolumes",
+            json!([
+                { "name": "workspace", "mountPath": "/workspace/.cache" },
+                { "name": "custom-ca", "mountPath": "/certs" },
+                { "name": "root", "mountPath": "/" },
+                { "name": "relative", "mountPath": "cache" },
+            ]),
+        );
+        assert_eq!(
+            validate_code_run_spec(&invalid).unwrap_err(),
+            "spec.extraVolumes name 'workspace' is reserved or duplicated; \
+             spec.extraVolumes mountPath '/workspace/.cache' must not overlap /workspace; \
+             spec.extraVolumes name 'custom-ca' is reserved or duplicated; \
+             spec.extraVolumes mountPath '/' must not overlap /workspace; \
+             spec.extraVolumes mountPath 'cache' must be absolute"
+        );
+    }
+
+    #[test]
+    fn test_sidecars_are_validated() {
+        let mut valid = spec_with(
+            "sidecars",
+            json!([
+                { "name": "toolman", "image": "ghcr.io/5dlabs/toolman:latest", "ports": [3000] },
+                {
+                    "name": "postgres",
+                    "image": "postgres:16",
+                    "ports": [5432],
+                    "env": { "POSTGRES_PASSWORD": "test" },
+                    "sharedVolumes": ["workspace"]
+                },
+            ]),
+        );
+        assert!(validate_code_run_spec(&valid).is_ok());
+
+        valid.sidecars[1].shared_volumes.push("cache".to_string());
+        valid.sidecars[1].ports.push(3000);
+        valid.sidecars[0].name = "claude-code".to_string();
+        valid.sidecars[0].image = " ".to_string();
+        assert_eq!(
+            validate_code_run_spec(&valid).unwrap_err(),
+            "spec.sidecars name 'claude-code' is reserved or duplicated; \
+             spec.sidecars 'claude-code' image must not be empty; \
+             spec.sidecars 'postgres' port 3000 is invalid or already used by another sidecar; \
+             spec.sidecars 'postgres' sharedVolumes entry 'cache' must be 'workspace' or an extraVolumes name"
+        );
+
+        let mut docker = spec_with(
+            "sidecars",
+            json!([{ "name": "registry", "image": "registry:2", "ports": [2375] }]),
+        );
+        assert!(validate_code_run_spec(&docker).is_ok());
+        docker.enable_docker = Some(true);
+        assert_eq!(
+            validate_code_run_spec(&docker).unwrap_err(),
+            "spec.sidecars 'registry' port 2375 is used by the Docker daemon (enableDocker)"
+        );
+    }
+
+    #[test]
+    fn test_working_branch_template_is_rendered_and_validated() {
+        let spec = spec_with(
+            "workingBranch",
+            json!("agent/{user}/task-{task_id}-{timestamp}"),
+        );
+        assert_eq!(
+            spec.render_feature_branch("20250101000000", None).unwrap(),
+            "agent/5DLabs-Rex/task-1-20250101000000"
+        );
+        assert_eq!(
+            spec_with("workingBranch", json!(""))
+                .render_feature_branch("", None)
+                .unwrap(),
+            "feature/task-1-implementation"
+        );
+        assert_eq!(
+            spec_with("workingBranch", json!(""))
+                .render_feature_branch("", Some("agent/{service}/{task_id}"))
+                .unwrap(),
+            "agent/api/1"
+        );
+
+        for (template, problem) in [
+            ("agent/{owner}", "unknown placeholder '{owner}'"),
+            ("agent/{task_id", "unclosed '{'"),
+            ("agent..{service}", "not a valid git branch name"),
+            ("{service}.lock", "not a valid git branch name"),
+        ] {
+            let error =
+                validate_code_run_spec(&spec_with("workingBranch", json!(template))).unwrap_err();
+            assert!(error.starts_with("spec.workingBranch: "), "{error}");
+            assert!(error.contains(problem), "{error}");
+        }
+    }
+
+    #[test]
+    fn test_commit_sha_must_be_hex() {
+        for sha in ["", "3f2a9c1", "3F2A9C1D4E5B6A7980716253443526170819AABB"] {
+            assert!(
+                validate_code_run_spec(&spec_with("commitSha", json!(sha))).is_ok(),
+                "{sha}"
+            );
+        }
+        for sha in [
+            "3f2a9c",
+            "main",
+            "3f2a9c1d4e5b6a7980716253443526170819aabbc",
+        ] {
+            let error = validate_code_run_spec(&spec_with("commitSha", json!(sha))).unwrap_err();
+            assert!(error.starts_with("spec.commitSha "), "{error}");
+        }
+    }
+
+    const NAMESPACE: &str = "agent-platform";
+    const CODERUNS: &str = "/apis/agents.platform/v1/namespaces/agent-platform/coderuns";
+    const CONFIGMAPS: &str = "/api/v1/namespaces/agent-platform/configmaps";
+    const JOBS: &str = "/apis/batch/v1/namespaces/agent-platform/jobs";
+
+    /// Seed a CodeRun that already carries the finalizer, so reconcile goes straight to Apply
+    fn seed_code_run(api: &FakeApiServer, spec: serde_json::Value) -> Arc<CodeRun> {
+        let code_run = json!({
+            "apiVersion": "agents.platform/v1",
+            "kind": "CodeRun",
+            "metadata": {
+                "name": "task-5",
+                "namespace": NAMESPACE,
+                "uid": "0f3c9a2e-5b1d-4c7e-9a8f-2d6b4e1c7a90",
+                "finalizers": [CODE_FINALIZER_NAME]
+            },
+            "spec": spec
+        });
+        api.insert(&format!("{CODERUNS}/task-5"), code_run.clone());
+        Arc::new(serde_json::from_value(code_run).unwrap())
+    }
+
+    fn spec_with_task(task_id: u32) -> serde_json::Value {
+        let mut spec = pending_spec();
+        spec["taskId"] = json!(task_id);
+        spec
+    }
+
+    fn pending_spec() -> serde_json::Value {
+        json!({
+            "taskId": 5,
+            "service": "api",
+            "repositoryUrl": "https://github.com/5dlabs/example",
+            "docsRepositoryUrl": "https://github.com/5dlabs/docs",
+            "model": "claude-sonnet-4-20250514",
+            "githubApp": "5DLabs-Rex"
+        })
+    }
+
+    #[test]
+    fn test_spec_settle_state_waits_per_generation() {
+        let settle = std::time::Duration::from_secs(10);
+        let now = chrono::Utc::now();
+        let mut code_run: CodeRun = serde_json::from_value(json!({
+            "apiVersion": "agents.platform/v1",
+            "kind": "CodeRun",
+            "metadata": { "name": "task-5", "generation": 2 },
+            "spec": pending_spec()
+        }))
+        .unwrap();
+        assert_eq!(
+            spec_settle_state(&code_run, settle, now),
+            SpecSettle::NewGeneration
+        );
+
+        code_run.status = Some(
+            serde_json::from_value(json!({
+                "phase": "Pending",
+                "observedGeneration": 2,
+                "generationObservedAt": (now - chrono::Duration::seconds(4)).to_rfc3339()
+            }))
+            .unwrap(),
+        );
+        assert_eq!(
+            spec_settle_state(&code_run, settle, now),
+            SpecSettle::Waiting(std::time::Duration::from_secs(6))
+        );
+        assert_eq!(
+            spec_settle_state(&code_run, settle, now + chrono::Duration::seconds(6)),
+            SpecSettle::Settled
+        );
+
+        // Another edit restarts the window
+        code_run.metadata.generation = Some(3);
+        assert_eq!(
+            spec_settle_state(&code_run, settle, now + chrono::Duration::seconds(6)),
+            SpecSettle::NewGeneration
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_waits_for_spec_to_settle_before_creating_job() {
+        let api = FakeApiServer::start().await;
+        let mut config = ControllerConfig::default();
+        config.job.spec_settle_seconds = Some(10);
+        let ctx = api.context(NAMESPACE, config);
+        let code_run = seed_code_run(&api, pending_spec());
+
+        let action = reconcile_code_run(code_run, ctx).await.unwrap();
+        assert_eq!(action, Action::requeue(std::time::Duration::from_secs(10)));
+        assert!(api.names_in(JOBS).is_empty());
+
+        let status = &api.get(&format!("{CODERUNS}/task-5")).unwrap()["status"];
+        assert_eq!(status["phase"], "Pending");
+        assert_eq!(status["observedGeneration"], 0);
+        assert!(status["generationObservedAt"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_pending_code_run_creates_configmap_and_job() {
+        let _templates = crate::tasks::template_check::use_chart_templates();
+        let api = FakeApiServer::start().await;
+        let ctx = api.context(NAMESPACE, ControllerConfig::default());
+        let code_run = seed_code_run(&api, pending_spec());
+
+        let action = reconcile_code_run(code_run.clone(), ctx).await.unwrap();
+        assert_eq!(action, Action::requeue(std::time::Duration::from_secs(30)));
+
+        let job_name = generate_code_job_name(&code_run);
+        assert_eq!(job_name, "code-agent-platform-task-5-0f3c9a2e-t5-v1");
+        let cm_name = "code-agent-platform-task-5-0f3c9a2e-api-t5-v1-files".to_string();
+        assert_eq!(api.names_in(JOBS), vec![job_name.clone()]);
+        assert_eq!(api.names_in(CONFIGMAPS), vec![cm_name.clone()]);
+        assert!(api
+            .get("/api/v1/namespaces/agent-platform/persistentvolumeclaims/workspace-api")
+            .is_some());
+
+        // The Job mounts the task files, which the Job then owns
+        let job = api.get(&format!("{JOBS}/{job_name}")).unwrap();
+        let volumes = job["spec"]["template"]["spec"]["volumes"]
+            .as_array()
+            .unwrap();
+        assert!(volumes
+            .iter()
+            .any(|volume| volume["configMap"]["name"] == json!(cm_name)));
+        assert_eq!(
+            job["spec"]["template"]["spec"]["containers"][0]["resources"],
+            json!({
+                "requests": { "cpu": "1", "memory": "2Gi" },
+                "limits": { "cpu": "4", "memory": "8Gi" }
+            })
+        );
+        let configmap = api.get(&format!("{CONFIGMAPS}/{cm_name}")).unwrap();
+        assert_eq!(
+            configmap["metadata"]["ownerReferences"][0]["name"],
+            json!(job_name)
+        );
+        // container.sh sources the shared CA trust script shipped beside it
+        let script = configmap["data"]["container.sh"].as_str().unwrap();
+        assert!(script.contains(". /task-files/ca-trust.sh"), "{script}");
+        assert!(configmap["data"]["ca-trust.sh"]
+            .as_str()
+            .is_some_and(|script| script.contains("CUSTOM_CA_BUNDLE")));
+
+        let status = &api.get(&format!("{CODERUNS}/task-5")).unwrap()["status"];
+        assert_eq!(status["phase"], "Running");
+        assert_eq!(status["jobName"], json!(job_name));
+        assert_eq!(status["configmapName"], json!(cm_name));
+        assert_eq!(status["totalAttempts"], 1);
+        assert!(configmap["metadata"]["annotations"]
+            [crate::tasks::configmap::CONTENT_HASH_ANNOTATION]
+            .is_string());
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_applies_default_working_branch_without_webhook() {
+        let _templates = crate::tasks::template_check::use_chart_templates();
+        let api = FakeApiServer::start().await;
+        let mut config = ControllerConfig::default();
+        config.defaults.working_branch = Some("agent/{user}/task-{task_id}".to_string());
+        let ctx = api.context(NAMESPACE, config);
+        let code_run = seed_code_run(&api, pending_spec());
+
+        reconcile_code_run(code_run, ctx).await.unwrap();
+
+        let status = &api.get(&format!("{CODERUNS}/task-5")).unwrap()["status"];
+        assert_eq!(status["gitBranch"], "agent/5DLabs-Rex/task-5");
+        let configmap = api
+            .get(&format!(
+                "{CONFIGMAPS}/{}",
+                status["configmapName"].as_str().unwrap()
+            ))
+            .unwrap();
+        let script = configmap["data"]["container.sh"].as_str().unwrap();
+        assert!(script.contains("'agent/5DLabs-Rex/task-5'"), "{script}");
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_keeps_job_created_under_legacy_name() {
+        let api = FakeApiServer::start().await;
+        let ctx = api.context(NAMESPACE, ControllerConfig::default());
+        let name = "implement-the-billing-reconciliation-service";
+        let mut code_run: CodeRun = serde_json::from_value(json!({
+            "apiVersion": "agents.platform/v1",
+            "kind": "CodeRun",
+            "metadata": {
+                "name": name,
+                "namespace": NAMESPACE,
+                "uid": "0f3c9a2e-5b1d-4c7e-9a8f-2d6b4e1c7a90",
+                "finalizers": [CODE_FINALIZER_NAME]
+            },
+            "spec": pending_spec()
+        }))
+        .unwrap();
+        let legacy = crate::tasks::types::legacy_resource_name(&code_job_key(&code_run));
+        assert_ne!(legacy, generate_code_job_name(&code_run));
+        code_run.status =
+            Some(serde_json::from_value(json!({ "phase": "Running", "jobName": legacy })).unwrap());
+        api.insert(
+            &format!("{CODERUNS}/{name}"),
+            serde_json::to_value(&code_run).unwrap(),
+        );
+        api.insert(
+            &format!("{JOBS}/{legacy}"),
+            json!({
+                "apiVersion": "batch/v1",
+                "kind": "Job",
+                "metadata": { "name": legacy, "namespace": NAMESPACE },
+                "status": { "active": 1 }
+            }),
+        );
+
+        reconcile_code_run(Arc::new(code_run), ctx).await.unwrap();
+
+        // The in-flight Job is monitored, not replaced by one under the capped name
+        assert_eq!(api.names_in(JOBS), vec![legacy.clone()]);
+        let status = &api.get(&format!("{CODERUNS}/{name}")).unwrap()["status"];
+        assert_eq!(status["phase"], "Running");
+        assert_eq!(status["jobName"], json!(legacy));
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_adopts_running_job_missing_from_status_once() {
+        let api = FakeApiServer::start().await;
+        let ctx = api.context(NAMESPACE, ControllerConfig::default());
+        let code_run = seed_code_run(&api, pending_spec());
+        let path = format!("{CODERUNS}/task-5");
+        let mut seeded = api.get(&path).unwrap();
+        // The controller restarted after creating the Job, before recording it
+        seeded["status"] = json!({ "phase": "Running" });
+        api.insert(&path, seeded);
+        let job_name = generate_code_job_name(&code_run);
+        let cm_name = "task-5-files-0123456789";
+        api.insert(
+            &format!("{CONFIGMAPS}/{cm_name}"),
+            json!({
+                "apiVersion": "v1",
+                "kind": "ConfigMap",
+                "metadata": { "name": cm_name, "namespace": NAMESPACE },
+                "data": { "container.sh": "#!/bin/sh" }
+            }),
+        );
+        api.insert(
+            &format!("{JOBS}/{job_name}"),
+            json!({
+                "apiVersion": "batch/v1",
+                "kind": "Job",
+                "metadata": {
+                    "name": job_name,
+                    "namespace": NAMESPACE,
+                    "uid": "7d2e4f1a-3c5b-4a9e-8f6d-1b2c3d4e5f60"
+                },
+                "spec": {
+                    "template": {
+                        "spec": {
+                            "containers": [],
+                            "volumes": [{ "name": "task-files", "configMap": { "name": cm_name } }]
+                        }
+                    }
+                },
+                "status": { "active": 1 }
+            }),
+        );
+
+        for _ in 0..2 {
+            let current = Arc::new(serde_json::from_value(api.get(&path).unwrap()).unwrap());
+            reconcile_code_run(current, ctx.clone()).await.unwrap();
+        }
+
+        assert_eq!(api.names_in(JOBS), vec![job_name.clone()]);
+        let status = &api.get(&path).unwrap()["status"];
+        assert_eq!(status["phase"], "Running");
+        assert_eq!(status["jobName"], json!(job_name));
+        assert_eq!(status["configmapName"], cm_name);
+        assert_eq!(status["totalAttempts"], 1);
+        let owners =
+            &api.get(&format!("{CONFIGMAPS}/{cm_name}")).unwrap()["metadata"]["ownerReferences"];
+        assert_eq!(owners.as_array().map(Vec::len), Some(1), "{owners}");
+        assert_eq!(owners[0]["name"], json!(job_name));
+    }
+
+    const WORKSPACE_PVC: &str =
+        "/api/v1/namespaces/agent-platform/persistentvolumeclaims/workspace-api";
+
+    fn seed_held_workspace(api: &FakeApiServer, preserved_for: &str) {
+        api.insert(
+            WORKSPACE_PVC,
+            json!({
+                "apiVersion": "v1",
+                "kind": "PersistentVolumeClaim",
+                "metadata": {
+                    "name": "workspace-api",
+                    "namespace": NAMESPACE,
+                    "annotations": { PRESERVED_WORKSPACE_ANNOTATION: preserved_for }
+                },
+                "spec": { "accessModes": ["ReadWriteOnce"] }
+            }),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deleting_failed_run_releases_preserved_workspace() {
+        let _templates = crate::tasks::template_check::use_chart_templates();
+        let api = FakeApiServer::start().await;
+        let ctx = api.context(NAMESPACE, ControllerConfig::default());
+        seed_held_workspace(&api, "task-4");
+        let failed = json!({
+            "apiVersion": "agents.platform/v1",
+            "kind": "CodeRun",
+            "metadata": {
+                "name": "task-4",
+                "namespace": NAMESPACE,
+                "uid": "7d0e1b52-9c3a-4f6e-8b21-5a4c3d2e1f00",
+                "finalizers": [CODE_FINALIZER_NAME]
+            },
+            "spec": spec_with_task(4),
+            "status": { "phase": "Failed", "workspacePvc": "workspace-api" }
+        });
+        api.insert(&format!("{CODERUNS}/task-4"), failed.clone());
+        let code_run = seed_code_run(&api, pending_spec());
+
+        // The failed run still exists, so the next run waits
+        let action = reconcile_code_run(code_run.clone(), ctx.clone())
+            .await
+            .unwrap();
+        assert_eq!(action, Action::requeue(std::time::Duration::from_secs(60)));
+        assert!(api.names_in(JOBS).is_empty());
+
+        cleanup_code_resources(Arc::new(serde_json::from_value(failed).unwrap()), &ctx)
+            .await
+            .unwrap();
+        assert!(api.get(WORKSPACE_PVC).unwrap()["metadata"]["annotations"]
+            [PRESERVED_WORKSPACE_ANNOTATION]
+            .is_null());
+
+        let code_run = Arc::new(
+            serde_json::from_value(api.get(&format!("{CODERUNS}/task-5")).unwrap()).unwrap(),
+        );
+        reconcile_code_run(code_run, ctx).await.unwrap();
+        assert_eq!(api.names_in(JOBS).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_releases_hold_for_missing_code_run() {
+        let _templates = crate::tasks::template_check::use_chart_templates();
+        let api = FakeApiServer::start().await;
+        let ctx = api.context(NAMESPACE, ControllerConfig::default());
+        seed_held_workspace(&api, "task-3");
+        let code_run = seed_code_run(&api, pending_spec());
+
+        reconcile_code_run(code_run, ctx).await.unwrap();
+
+        assert_eq!(api.names_in(JOBS).len(), 1);
+        assert!(api.get(WORKSPACE_PVC).unwrap()["metadata"]["annotations"]
+            [PRESERVED_WORKSPACE_ANNOTATION]
+            .is_null());
+    }
+
+    #[tokio::test]
+    async fn test_since_failure_retry_uses_recorded_log_tail_after_job_is_gone() {
+        let _templates = crate::tasks::template_check::use_chart_templates();
+        let api = FakeApiServer::start().await;
+        let ctx = api.context(NAMESPACE, ControllerConfig::default());
+        // The failed attempt's Job and pods were removed by its TTL; only its status is left
+        api.insert(
+            &format!("{CODERUNS}/task-5-first"),
+            json!({
+                "apiVersion": "agents.platform/v1",
+                "kind": "CodeRun",
+                "metadata": {
+                    "name": "task-5-first",
+                    "namespace": NAMESPACE,
+                    "uid": "7d0e1b52-9c3a-4f6e-8b21-5a4c3d2e1f00"
+                },
+                "spec": pending_spec(),
+                "status": {
+                    "phase": "Failed",
+                    "jobName": "code-agent-platform-task-5-first-7d0e1b52-t5-v1",
+                    "lastUpdate": "2026-10-18T09:00:00Z",
+                    "failureLogTail": "cargo test\nerror[E0308]: mismatched types\n"
+                }
+            }),
+        );
+        let mut spec = pending_spec();
+        spec["sinceFailure"] = json!(true);
+        let code_run = seed_code_run(&api, spec);
+
+        reconcile_code_run(code_run, ctx).await.unwrap();
+
+        let cm_name = &api.names_in(CONFIGMAPS)[0];
+        let configmap = api.get(&format!("{CONFIGMAPS}/{cm_name}")).unwrap();
+        let previous = configmap["data"]["previous-failure.md"].as_str().unwrap();
+        assert!(previous.contains("`task-5-first`"), "{previous}");
+        assert!(
+            previous.contains("error[E0308]: mismatched types"),
+            "{previous}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_changed_immutable_configmap_goes_to_a_new_name() {
+        let _templates = crate::tasks::template_check::use_chart_templates();
+        let api = FakeApiServer::start().await;
+        let mut config = ControllerConfig::default();
+        config.configmap.immutable = true;
+        let ctx = api.context(NAMESPACE, config);
+        let code_run = seed_code_run(&api, pending_spec());
+
+        reconcile_code_run(code_run.clone(), ctx.clone())
+            .await
+            .unwrap();
+        let first_cm = api.names_in(CONFIGMAPS).remove(0);
+        let first = api.get(&format!("{CONFIGMAPS}/{first_cm}")).unwrap();
+        assert_eq!(first["immutable"], true);
+
+        // The Job's TTL removes it, then the spec changes what gets rendered
+        api.remove(&format!("{JOBS}/{}", generate_code_job_name(&code_run)));
+        let path = format!("{CODERUNS}/task-5");
+        let mut edited = api.get(&path).unwrap();
+        edited["spec"]["model"] = json!("claude-opus-4-20250514");
+        api.insert(&path, edited.clone());
+        reconcile_code_run(Arc::new(serde_json::from_value(edited).unwrap()), ctx)
+            .await
+            .unwrap();
+
+        // The first ConfigMap is left as it was, and the new Job mounts the new one
+        let names = api.names_in(CONFIGMAPS);
+        assert_eq!(names.len(), 2, "{names:?}");
+        assert_eq!(api.get(&format!("{CONFIGMAPS}/{first_cm}")).unwrap(), first);
+        let second_cm = names.into_iter().find(|name| *name != first_cm).unwrap();
+        let job = api
+            .get(&format!("{JOBS}/{}", generate_code_job_name(&code_run)))
+            .unwrap();
+        let job: Job = serde_json::from_value(job).unwrap();
+        assert_eq!(
+            crate::tasks::configmap::job_task_configmap(&job),
+            Some(second_cm.clone())
+        );
+        let status = &api.get(&path).unwrap()["status"];
+        assert_eq!(status["configmapName"], json!(second_cm));
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_stops_at_max_total_attempts() {
+        let api = FakeApiServer::start().await;
+        let mut config = ControllerConfig::default();
+        config.job.max_total_attempts = Some(3);
+        let ctx = api.context(NAMESPACE, config);
+        seed_code_run(&api, pending_spec());
+        let path = format!("{CODERUNS}/task-5");
+        let mut seeded = api.get(&path).unwrap();
+        seeded["status"] = json!({ "phase": "Pending", "totalAttempts": 3 });
+        api.insert(&path, seeded.clone());
+        let code_run = Arc::new(serde_json::from_value(seeded).unwrap());
+
+        let action = reconcile_code_run(code_run, ctx).await.unwrap();
+        assert_eq!(action, Action::await_change());
+
+        assert!(api.names_in(JOBS).is_empty());
+        assert!(api.names_in(CONFIGMAPS).is_empty());
+        let status = &api.get(&path).unwrap()["status"];
+        assert_eq!(status["phase"], "Failed");
+        assert_eq!(status["totalAttempts"], 3);
+        assert_eq!(status["conditions"][0]["reason"], "MaxAttemptsExceeded");
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_invalid_code_run_fails_without_creating_resources() {
+        let api = FakeApiServer::start().await;
+        let ctx = api.context(NAMESPACE, ControllerConfig::default());
+        let mut spec = pending_spec();
+        spec["commitSha"] = json!("main");
+        let code_run = seed_code_run(&api, spec);
+
+        let action = reconcile_code_run(code_run, ctx).await.unwrap();
+        assert_eq!(action, Action::await_change());
+
+        assert!(api.names_in(JOBS).is_empty());
+        assert!(api.names_in(CONFIGMAPS).is_empty());
+        let status = &api.get(&format!("{CODERUNS}/task-5")).unwrap()["status"];
+        assert_eq!(status["phase"], "Failed");
+        assert_eq!(status["conditions"][0]["reason"], "InvalidSpec");
+    }
+}
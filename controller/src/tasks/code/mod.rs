@@ -1,5 +1,6 @@
 pub mod controller;
 pub mod resources;
+pub mod retention;
 pub mod status;
 pub mod templates;
 
@@ -1,9 +1,17 @@
-use crate::crds::CodeRun;
-use crate::tasks::config::ControllerConfig;
-use crate::tasks::types::{github_app_secret_name, Context, Result};
+use super::controller::{generate_code_job_name, latest_job_pod_logs};
+use crate::crds::{CodeRun, Sidecar};
+use crate::tasks::config::{ControllerConfig, StorageConfig};
+use crate::tasks::configmap::{
+    add_owner_reference, annotate_content, job_owner_reference, job_task_configmap,
+    plan_configmap_update, version_immutable_configmap, ConfigMapUpdate,
+};
+use crate::tasks::types::{
+    build_resource, github_app_secret_name, resource_name, Context, KubeResultExt, Result,
+};
 use k8s_openapi::api::{
     batch::v1::Job,
     core::v1::{ConfigMap, PersistentVolumeClaim},
+    storage::v1::StorageClass,
 };
 use k8s_openapi::apimachinery::pkg::apis::meta::v1::{ObjectMeta, OwnerReference};
 use kube::api::{Api, DeleteParams, ListParams, PostParams};
@@ -12,7 +20,23 @@ use kube::ResourceExt;
 use serde_json::json;
 use std::collections::BTreeMap;
 use std::sync::Arc;
-use tracing::{error, info};
+use tracing::{error, info, warn};
+
+/// Provisioners that only offer single-node volumes, so can't back `ReadWriteMany` claims
+const SINGLE_NODE_PROVISIONERS: &[&str] = &[
+    "rancher.io/local-path",
+    "kubernetes.io/no-provisioner",
+    "ebs.csi.aws.com",
+    "kubernetes.io/aws-ebs",
+    "pd.csi.storage.gke.io",
+    "kubernetes.io/gce-pd",
+    "disk.csi.azure.com",
+    "kubernetes.io/azure-disk",
+];
+
+/// Bounds on the failed-attempt log tail handed to the next attempt
+pub(super) const PREVIOUS_FAILURE_LOG_LINES: i64 = 200;
+const PREVIOUS_FAILURE_LOG_BYTES: usize = 16 * 1024;
 
 pub struct CodeResourceManager<'a> {
     pub jobs: &'a Api<Job>,
@@ -41,6 +65,7 @@ impl<'a> CodeResourceManager<'a> {
 
     pub async fn reconcile_create_or_update(&self, code_run: &Arc<CodeRun>) -> Result<Action> {
         let name = code_run.name_any();
+        let namespace = self.ctx.namespace.as_str();
         info!("🚀 Creating/updating code resources for: {}", name);
 
         // Ensure PVC exists for code tasks (persistent workspace)
@@ -58,7 +83,18 @@ impl<'a> CodeResourceManager<'a> {
         info!("📄 Generated ConfigMap name: {}", cm_name);
 
         info!("🔧 Creating ConfigMap template data...");
-        let configmap = self.create_configmap(code_run, &cm_name, None)?;
+        let mut configmap = self.create_configmap(code_run, &cm_name, None)?;
+        if code_run.spec.since_failure {
+            if let Some(previous_failure) = self.previous_failure_context(code_run).await {
+                configmap
+                    .data
+                    .get_or_insert_with(BTreeMap::new)
+                    .insert("previous-failure.md".to_string(), previous_failure);
+                info!("📎 Added previous failure logs to ConfigMap");
+            }
+        }
+        annotate_content(&mut configmap);
+        let cm_name = version_immutable_configmap(&mut configmap);
         info!("✅ ConfigMap template created successfully");
 
         // Always create or update ConfigMap to ensure latest template content
@@ -77,23 +113,41 @@ impl<'a> CodeResourceManager<'a> {
                     "📝 ConfigMap exists, updating with latest content: {}",
                     cm_name
                 );
-                match self
-                    .configmaps
-                    .replace(&cm_name, &PostParams::default(), &configmap)
-                    .await
-                {
-                    Ok(_) => {
-                        info!("✅ Updated ConfigMap: {}", cm_name);
+                let existing_cm = self.configmaps.get(&cm_name).await.for_resource(
+                    "get",
+                    "ConfigMap",
+                    &cm_name,
+                    namespace,
+                )?;
+                match plan_configmap_update(&existing_cm, &configmap) {
+                    ConfigMapUpdate::Unchanged => {
+                        info!("✅ ConfigMap already up to date: {}", cm_name);
                     }
-                    Err(e) => {
-                        error!("❌ Failed to update ConfigMap {}: {}", cm_name, e);
-                        return Err(e.into());
+                    ConfigMapUpdate::Replace => {
+                        match self
+                            .configmaps
+                            .replace(&cm_name, &PostParams::default(), &configmap)
+                            .await
+                        {
+                            Ok(_) => {
+                                info!("✅ Updated ConfigMap: {}", cm_name);
+                            }
+                            Err(e) => {
+                                error!("❌ Failed to update ConfigMap {}: {}", cm_name, e);
+                                return Err(e).for_resource(
+                                    "replace",
+                                    "ConfigMap",
+                                    &cm_name,
+                                    namespace,
+                                );
+                            }
+                        }
                     }
                 }
             }
             Err(e) => {
                 error!("❌ Failed to create ConfigMap {}: {}", cm_name, e);
-                return Err(e.into());
+                return Err(e).for_resource("create", "ConfigMap", &cm_name, namespace);
             }
         }
 
@@ -116,6 +170,38 @@ impl<'a> CodeResourceManager<'a> {
         Ok(Action::await_change())
     }
 
+    /// Take over a Job created before a controller restart that status never recorded:
+    /// record it as started and give its ConfigMap the owner reference, without
+    /// touching the Job or the ConfigMap content it's running with
+    pub async fn adopt_job(&self, code_run: &Arc<CodeRun>, job: &Job) -> Result<()> {
+        let job_name = job.name_any();
+        // The Job names the ConfigMap it mounts, whether versioned or under a legacy name
+        let cm_name =
+            job_task_configmap(job).unwrap_or_else(|| self.generate_configmap_name(code_run));
+        info!("🔁 Adopting existing job {} after restart", job_name);
+
+        if let Some(owner_ref) = job_owner_reference(job) {
+            match self
+                .update_configmap_owner(code_run, &cm_name, owner_ref)
+                .await
+            {
+                Ok(()) => {}
+                Err(e) if e.api_status() == Some(404) => {
+                    warn!(
+                        "ConfigMap {} for adopted job {} is missing",
+                        cm_name, job_name
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        super::status::CodeStatusManager::update_job_started(
+            code_run, self.ctx, &job_name, &cm_name,
+        )
+        .await
+    }
+
     pub async fn cleanup_resources(&self, code_run: &Arc<CodeRun>) -> Result<Action> {
         let name = code_run.name_any();
         info!("Cleaning up code resources for: {}", name);
@@ -128,14 +214,51 @@ impl<'a> CodeResourceManager<'a> {
     }
 
     async fn ensure_pvc_exists(&self, pvc_name: &str, service_name: &str) -> Result<()> {
+        let access_mode = &self.config.storage.access_mode;
         match self.pvcs.get(pvc_name).await {
-            Ok(_) => {
+            Ok(pvc) => {
                 info!("PVC {} already exists", pvc_name);
+                // Access modes are immutable, so a changed setting only applies to new PVCs
+                let modes = pvc
+                    .spec
+                    .as_ref()
+                    .and_then(|spec| spec.access_modes.clone())
+                    .unwrap_or_default();
+                if !modes.contains(access_mode) {
+                    warn!(
+                        "PVC {} has access modes {:?}, not the configured {}; delete it to recreate",
+                        pvc_name, modes, access_mode
+                    );
+                }
+                // Nor is a size increase applied to an existing claim
+                let configured_size = self.config.storage.workspace_size_for(service_name);
+                let requested_size = pvc
+                    .spec
+                    .as_ref()
+                    .and_then(|spec| spec.resources.as_ref())
+                    .and_then(|resources| resources.requests.as_ref())
+                    .and_then(|requests| requests.get("storage"))
+                    .map(|quantity| quantity.0.as_str());
+                if let Some(requested) = requested_size {
+                    if let (Some(have), Some(want)) =
+                        (quantity_bytes(requested), quantity_bytes(configured_size))
+                    {
+                        if have < want {
+                            warn!(
+                                "PVC {} requests {}, smaller than the configured {}; delete it to recreate",
+                                pvc_name, requested, configured_size
+                            );
+                        }
+                    }
+                }
                 Ok(())
             }
             Err(kube::Error::Api(ae)) if ae.code == 404 => {
-                info!("Creating PVC: {}", pvc_name);
-                let pvc = self.build_pvc_spec(pvc_name, service_name);
+                if access_mode == "ReadWriteMany" {
+                    self.check_storage_class_supports_rwx(service_name).await?;
+                }
+                info!("Creating PVC: {} ({})", pvc_name, access_mode);
+                let pvc = build_workspace_pvc(pvc_name, service_name, &self.config.storage);
                 match self.pvcs.create(&PostParams::default(), &pvc).await {
                     Ok(_) => {
                         info!("Successfully created PVC: {}", pvc_name);
@@ -145,62 +268,65 @@ impl<'a> CodeResourceManager<'a> {
                         info!("PVC {} was created concurrently", pvc_name);
                         Ok(())
                     }
-                    Err(e) => Err(e.into()),
+                    Err(e) => Err(e).for_resource(
+                        "create",
+                        "PersistentVolumeClaim",
+                        pvc_name,
+                        &self.ctx.namespace,
+                    ),
                 }
             }
-            Err(e) => Err(e.into()),
+            Err(e) => Err(e).for_resource(
+                "get",
+                "PersistentVolumeClaim",
+                pvc_name,
+                &self.ctx.namespace,
+            ),
         }
     }
 
-    fn build_pvc_spec(&self, pvc_name: &str, service_name: &str) -> PersistentVolumeClaim {
-        let mut spec = json!({
-            "accessModes": ["ReadWriteOnce"],
-            "resources": {
-                "requests": {
-                    "storage": self.config.storage.workspace_size.clone()
-                }
-            }
-        });
+    /// Best-effort check that the workspace storage class can serve `ReadWriteMany` claims.
+    /// Only known single-node provisioners are rejected; if the class can't be read (e.g.
+    /// no RBAC for cluster-scoped StorageClasses) the configured class name is checked instead.
+    async fn check_storage_class_supports_rwx(&self, service_name: &str) -> Result<()> {
+        let classes: Api<StorageClass> = Api::all(self.ctx.client.clone());
+        let configured = self.config.storage.storage_class_for(service_name);
+        let class = match configured {
+            Some(name) => classes.get_opt(name).await.ok().flatten(),
+            None => classes
+                .list(&ListParams::default())
+                .await
+                .ok()
+                .and_then(|list| {
+                    list.items.into_iter().find(|class| {
+                        class
+                            .annotations()
+                            .get("storageclass.kubernetes.io/is-default-class")
+                            .is_some_and(|value| value == "true")
+                    })
+                }),
+        };
 
-        // Add storageClassName if specified in config
-        if let Some(ref storage_class) = self.config.storage.storage_class_name {
-            spec["storageClassName"] = json!(storage_class);
+        let provisioner = match &class {
+            Some(class) => class.provisioner.as_str(),
+            None if configured == Some("local-path") => "rancher.io/local-path",
+            None => {
+                info!("Could not read the workspace storage class, assuming it supports ReadWriteMany");
+                return Ok(());
+            }
+        };
+        if SINGLE_NODE_PROVISIONERS.contains(&provisioner) {
+            return Err(crate::tasks::types::Error::ConfigError(format!(
+                "storage.accessMode is ReadWriteMany but storage class {} uses {}, which only supports ReadWriteOnce",
+                configured.unwrap_or("(cluster default)"),
+                provisioner
+            )));
         }
-
-        let pvc_spec = json!({
-            "apiVersion": "v1",
-            "kind": "PersistentVolumeClaim",
-            "metadata": {
-                "name": pvc_name,
-                "labels": {
-                    "app": "orchestrator",
-                    "component": "code-runner",
-                    "service": service_name
-                }
-            },
-            "spec": spec
-        });
-
-        serde_json::from_value(pvc_spec).expect("Failed to build PVC spec")
+        Ok(())
     }
 
     fn generate_configmap_name(&self, code_run: &CodeRun) -> String {
-        // Generate unique ConfigMap name per CodeRun to prevent conflicts between sequential jobs
-        let namespace = code_run.metadata.namespace.as_deref().unwrap_or("default");
-        let name = code_run.metadata.name.as_deref().unwrap_or("unknown");
-        let uid_suffix = code_run
-            .metadata
-            .uid
-            .as_deref()
-            .map(|uid| &uid[..8]) // Use first 8 chars of UID for uniqueness
-            .unwrap_or("nouid");
-        let task_id = code_run.spec.task_id;
-        let service_name = code_run.spec.service.replace('_', "-");
-        let context_version = code_run.spec.context_version;
-
-        format!("code-{namespace}-{name}-{uid_suffix}-{service_name}-t{task_id}-v{context_version}-files")
-            .replace(['_', '.'], "-")
-            .to_lowercase()
+        resource_name(&configmap_key(code_run))
     }
 
     fn create_configmap(
@@ -232,17 +358,56 @@ impl<'a> CodeResourceManager<'a> {
         Ok(ConfigMap {
             metadata,
             data: Some(data),
+            immutable: self.config.configmap.immutable.then_some(true),
             ..Default::default()
         })
     }
 
+    /// Markdown with the log tail of the most recent failed attempt at this task and service
+    ///
+    /// Uses the tail recorded in the failed run's status when it failed. Runs that failed
+    /// before it was recorded fall back to their pods, which the Job's TTL may have removed.
+    async fn previous_failure_context(&self, code_run: &CodeRun) -> Option<String> {
+        let code_runs: Api<CodeRun> = Api::namespaced(self.ctx.client.clone(), &self.ctx.namespace);
+        let runs = match code_runs.list(&ListParams::default()).await {
+            Ok(runs) => runs,
+            Err(e) => {
+                warn!("Could not list CodeRuns for previous failure: {}", e);
+                return None;
+            }
+        };
+
+        let (failed_run, job_name, recorded_tail) = runs
+            .items
+            .iter()
+            .filter(|run| {
+                run.spec.task_id == code_run.spec.task_id
+                    && run.spec.service == code_run.spec.service
+            })
+            .filter_map(|run| {
+                let status = run.status.as_ref().filter(|s| s.phase == "Failed")?;
+                Some((run, status.job_name.clone()?, status.last_update.clone()))
+            })
+            .max_by(|a, b| a.2.cmp(&b.2))
+            .map(|(run, job_name, _)| {
+                let tail = run.status.as_ref().and_then(|s| s.failure_log_tail.clone());
+                (run.name_any(), job_name, tail)
+            })?;
+
+        let logs = match recorded_tail {
+            Some(tail) => tail,
+            None => latest_job_pod_logs(self.ctx, &job_name, PREVIOUS_FAILURE_LOG_LINES).await?,
+        };
+        Some(format_previous_failure(&failed_run, &job_name, &logs))
+    }
+
     /// Idempotent job creation: create if doesn't exist, get if it does
     async fn create_or_get_job(
         &self,
         code_run: &CodeRun,
         cm_name: &str,
     ) -> Result<Option<OwnerReference>> {
-        let job_name = self.generate_job_name(code_run);
+        let job_name = generate_code_job_name(code_run);
 
         // Try to get existing job first (idempotent check)
         match self.jobs.get(&job_name).await {
@@ -274,14 +439,7 @@ impl<'a> CodeResourceManager<'a> {
                 }
 
                 // Return the existing job's owner reference
-                Ok(Some(OwnerReference {
-                    api_version: "batch/v1".to_string(),
-                    kind: "Job".to_string(),
-                    name: job_name,
-                    uid: existing_job.metadata.uid.unwrap_or_default(),
-                    controller: Some(false),
-                    block_owner_deletion: Some(true),
-                }))
+                Ok(job_owner_reference(&existing_job))
             }
             Err(_) => {
                 // Job doesn't exist, create it
@@ -296,7 +454,7 @@ impl<'a> CodeResourceManager<'a> {
         code_run: &CodeRun,
         cm_name: &str,
     ) -> Result<Option<OwnerReference>> {
-        let job_name = self.generate_job_name(code_run);
+        let job_name = generate_code_job_name(code_run);
         let job = self.build_job_spec(code_run, &job_name, cm_name)?;
 
         match self.jobs.create(&PostParams::default(), &job).await {
@@ -350,48 +508,7 @@ impl<'a> CodeResourceManager<'a> {
                     Err(_) => Ok(None),
                 }
             }
-            Err(e) => Err(e.into()),
-        }
-    }
-
-    fn generate_job_name(&self, code_run: &CodeRun) -> String {
-        // Use deterministic naming based on the CodeRun's actual name and UID
-        // This ensures the same CodeRun always generates the same Job name
-        let namespace = code_run.metadata.namespace.as_deref().unwrap_or("default");
-        let name = code_run.metadata.name.as_deref().unwrap_or("unknown");
-        let uid_suffix = code_run
-            .metadata
-            .uid
-            .as_deref()
-            .map(|uid| &uid[..8]) // Use first 8 chars of UID for uniqueness
-            .unwrap_or("nouid");
-        let task_id = code_run.spec.task_id;
-        let context_version = code_run.spec.context_version;
-
-        let job_name =
-            format!("code-{namespace}-{name}-{uid_suffix}-t{task_id}-v{context_version}")
-                .replace(['_', '.'], "-")
-                .to_lowercase();
-
-        // Kubernetes has a 63-character limit for resource names and labels
-        // Truncate if necessary while preserving uniqueness
-        if job_name.len() > 63 {
-            let uid_and_suffix = format!("-{uid_suffix}-t{task_id}-v{context_version}");
-            let available_len = 63 - uid_and_suffix.len();
-            let prefix = format!("code-{namespace}-{name}")
-                .replace(['_', '.'], "-")
-                .to_lowercase();
-
-            if prefix.len() > available_len {
-                format!(
-                    "{}-{uid_suffix}-t{task_id}-v{context_version}",
-                    &prefix[..available_len]
-                )
-            } else {
-                job_name
-            }
-        } else {
-            job_name
+            Err(e) => Err(e).for_resource("create", "Job", &job_name, &self.ctx.namespace),
         }
     }
 
@@ -457,6 +574,29 @@ impl<'a> CodeResourceManager<'a> {
             "mountPath": "/workspace"
         }));
 
+        // User-requested volumes (shared caches etc.); mount paths are checked by validate_code_run_spec
+        for extra in &code_run.spec.extra_volumes {
+            volumes.push(match &extra.claim_name {
+                Some(claim_name) => json!({
+                    "name": extra.name,
+                    "persistentVolumeClaim": { "claimName": claim_name }
+                }),
+                None => json!({ "name": extra.name, "emptyDir": {} }),
+            });
+            volume_mounts.push(json!({
+                "name": extra.name,
+                "mountPath": extra.mount_path,
+                "readOnly": extra.read_only
+            }));
+        }
+
+        // Extra CA certificates for TLS-intercepting proxies; container.sh installs them
+        let ca_bundle = self.config.agent.ca_bundle.as_ref();
+        if let Some(ca_bundle) = ca_bundle {
+            volumes.push(ca_bundle.volume());
+            volume_mounts.push(ca_bundle.volume_mount());
+        }
+
         // Docker-in-Docker volumes (disabled by default, can be enabled by setting enableDocker: true)
         let enable_docker = code_run.spec.enable_docker.unwrap_or(false);
         if enable_docker {
@@ -501,7 +641,8 @@ impl<'a> CodeResourceManager<'a> {
                 "valueFrom": {
                     "secretKeyRef": {
                         "name": github_app_secret_name(github_app),
-                        "key": "app-id"
+                        "key": "app-id",
+                        "optional": true
                     }
                 }
             }),
@@ -510,7 +651,8 @@ impl<'a> CodeResourceManager<'a> {
                 "valueFrom": {
                     "secretKeyRef": {
                         "name": github_app_secret_name(github_app),
-                        "key": "private-key"
+                        "key": "private-key",
+                        "optional": true
                     }
                 }
             }),
@@ -535,6 +677,16 @@ impl<'a> CodeResourceManager<'a> {
                 "value": "unix:///var/run/docker.sock"
             }));
         }
+        if let Some(ca_bundle) = ca_bundle {
+            final_env_vars.push(ca_bundle.env_var());
+        }
+
+        let sidecars: Vec<serde_json::Value> = code_run
+            .spec
+            .sidecars
+            .iter()
+            .map(|sidecar| sidecar_container(sidecar, &volume_mounts))
+            .collect();
 
         // Build the job spec with environment configuration
         let mut container_spec = json!({
@@ -544,7 +696,8 @@ impl<'a> CodeResourceManager<'a> {
             "command": ["/bin/bash"],
             "args": ["/task-files/container.sh"],
             "workingDir": "/workspace",
-            "volumeMounts": volume_mounts
+            "volumeMounts": volume_mounts,
+            "resources": self.config.agent.resources.code
         });
 
         // Add envFrom if we have secrets to mount
@@ -595,7 +748,7 @@ impl<'a> CodeResourceManager<'a> {
             containers.push(docker_daemon_spec);
         }
 
-        let job_spec = json!({
+        let mut job_spec = json!({
             "apiVersion": "batch/v1",
             "kind": "Job",
             "metadata": {
@@ -613,6 +766,13 @@ impl<'a> CodeResourceManager<'a> {
             "spec": {
                 "backoffLimit": 0,
                 "ttlSecondsAfterFinished": 30,
+                // Record disruptions (e.g. preemption) on the Job so they outlive the pod
+                "podFailurePolicy": {
+                    "rules": [{
+                        "action": "FailJob",
+                        "onPodConditions": [{ "type": "DisruptionTarget" }]
+                    }]
+                },
                 "template": {
                     "metadata": {
                         "labels": labels
@@ -626,7 +786,11 @@ impl<'a> CodeResourceManager<'a> {
             }
         });
 
-        Ok(serde_json::from_value(job_spec)?)
+        if !sidecars.is_empty() {
+            job_spec["spec"]["template"]["spec"]["initContainers"] = json!(sidecars);
+        }
+
+        build_resource("Job", job_spec)
     }
 
     fn process_task_requirements(
@@ -766,6 +930,10 @@ impl<'a> CodeResourceManager<'a> {
             "context-version".to_string(),
             code_run.spec.context_version.to_string(),
         );
+        labels.insert(
+            "owner".to_string(),
+            self.sanitize_label_value(self.config.owner_for(code_run.spec.github_app.as_deref())),
+        );
 
         // Code-specific labels
         labels.insert("task-type".to_string(), "code".to_string());
@@ -784,19 +952,23 @@ impl<'a> CodeResourceManager<'a> {
         cm_name: &str,
         owner_ref: OwnerReference,
     ) -> Result<()> {
-        let mut existing_cm = self.configmaps.get(cm_name).await?;
-
-        // Add owner reference
-        let owner_refs = existing_cm
-            .metadata
-            .owner_references
-            .get_or_insert_with(Vec::new);
-        owner_refs.push(owner_ref);
+        let mut existing_cm = self.configmaps.get(cm_name).await.for_resource(
+            "get",
+            "ConfigMap",
+            cm_name,
+            &self.ctx.namespace,
+        )?;
+
+        if !add_owner_reference(&mut existing_cm, owner_ref) {
+            info!("ConfigMap {} already owned by its Job", cm_name);
+            return Ok(());
+        }
 
         // Update the ConfigMap
         self.configmaps
             .replace(cm_name, &PostParams::default(), &existing_cm)
-            .await?;
+            .await
+            .for_resource("replace", "ConfigMap", cm_name, &self.ctx.namespace)?;
         info!("Updated ConfigMap {} with owner reference", cm_name);
 
         Ok(())
@@ -921,3 +1093,255 @@ impl<'a> CodeResourceManager<'a> {
         sanitized
     }
 }
+
+/// The end of a failed agent's log, bounded for storing in status and the next ConfigMap
+pub(super) fn failure_log_tail(logs: &str) -> &str {
+    // Keep the end of the log, where the failure is
+    let mut start = logs.len().saturating_sub(PREVIOUS_FAILURE_LOG_BYTES);
+    while !logs.is_char_boundary(start) {
+        start += 1;
+    }
+    &logs[start..]
+}
+
+fn format_previous_failure(run_name: &str, job_name: &str, logs: &str) -> String {
+    let tail = failure_log_tail(logs);
+    let truncated = tail.len() < logs.len();
+    let tail = tail.trim_end();
+
+    format!(
+        "# Previous Attempt Failed\n\n\
+         CodeRun `{run_name}` (job `{job_name}`) failed on this task. These are the last lines \
+         of its output{}. Work out why it failed before repeating the same approach.\n\n\
+         ```text\n{tail}\n```\n",
+        if truncated { ", truncated" } else { "" }
+    )
+}
+
+/// Workspace PVC for `service_name`, using its storage overrides if any
+fn build_workspace_pvc(
+    pvc_name: &str,
+    service_name: &str,
+    storage: &StorageConfig,
+) -> PersistentVolumeClaim {
+    let mut spec = json!({
+        "accessModes": [storage.access_mode],
+        "resources": {
+            "requests": {
+                "storage": storage.workspace_size_for(service_name)
+            }
+        }
+    });
+
+    if let Some(storage_class) = storage.storage_class_for(service_name) {
+        spec["storageClassName"] = json!(storage_class);
+    }
+
+    let pvc_spec = json!({
+        "apiVersion": "v1",
+        "kind": "PersistentVolumeClaim",
+        "metadata": {
+            "name": pvc_name,
+            "labels": {
+                "app": "orchestrator",
+                "component": "code-runner",
+                "service": service_name
+            }
+        },
+        "spec": spec
+    });
+
+    serde_json::from_value(pvc_spec).expect("Failed to build PVC spec")
+}
+
+/// Bytes in a Kubernetes quantity such as `10Gi`, `500M` or `1073741824`
+fn quantity_bytes(quantity: &str) -> Option<u128> {
+    const SUFFIXES: &[(&str, u128)] = &[
+        ("Ki", 1 << 10),
+        ("Mi", 1 << 20),
+        ("Gi", 1 << 30),
+        ("Ti", 1 << 40),
+        ("Pi", 1 << 50),
+        ("k", 1_000),
+        ("M", 1_000_000),
+        ("G", 1_000_000_000),
+        ("T", 1_000_000_000_000),
+        ("P", 1_000_000_000_000_000),
+    ];
+    let quantity = quantity.trim();
+    let (number, multiplier) = SUFFIXES
+        .iter()
+        .find_map(|(suffix, multiplier)| {
+            quantity
+                .strip_suffix(suffix)
+                .map(|number| (number, *multiplier))
+        })
+        .unwrap_or((quantity, 1));
+    number.parse::<u128>().ok().map(|n| n * multiplier)
+}
+
+/// Native sidecar (an always-restarting init container) for a `spec.sidecars` entry.
+///
+/// Kubernetes (1.29+) starts it before the agent and stops it once the agent exits, so the Job
+/// still completes. Older API servers drop `restartPolicy`, so the init container never exits
+/// and the agent never starts. Shared volumes mount where the agent has them.
+fn sidecar_container(sidecar: &Sidecar, agent_mounts: &[serde_json::Value]) -> serde_json::Value {
+    let volume_mounts: Vec<&serde_json::Value> = agent_mounts
+        .iter()
+        .filter(|mount| mount.get("subPath").is_none())
+        .filter(|mount| {
+            sidecar
+                .shared_volumes
+                .iter()
+                .any(|volume| mount["name"] == volume.as_str())
+        })
+        .collect();
+    let mut env: Vec<(&String, &String)> = sidecar.env.iter().collect();
+    env.sort();
+
+    let mut container = json!({
+        "name": sidecar.name,
+        "image": sidecar.image,
+        "restartPolicy": "Always",
+        "env": env
+            .into_iter()
+            .map(|(name, value)| json!({ "name": name, "value": value }))
+            .collect::<Vec<_>>(),
+        "ports": sidecar
+            .ports
+            .iter()
+            .map(|port| json!({ "containerPort": port }))
+            .collect::<Vec<_>>(),
+        "volumeMounts": volume_mounts
+    });
+    if !sidecar.command.is_empty() {
+        container["command"] = json!(sidecar.command);
+    }
+    if !sidecar.args.is_empty() {
+        container["args"] = json!(sidecar.args);
+    }
+    container
+}
+
+/// ConfigMap name for `code_run` before it is made a valid, length-capped resource name
+fn configmap_key(code_run: &CodeRun) -> String {
+    // Generate unique ConfigMap name per CodeRun to prevent conflicts between sequential jobs
+    let namespace = code_run.metadata.namespace.as_deref().unwrap_or("default");
+    let name = code_run.metadata.name.as_deref().unwrap_or("unknown");
+    let uid_suffix = code_run
+        .metadata
+        .uid
+        .as_deref()
+        .map(|uid| &uid[..8]) // Use first 8 chars of UID for uniqueness
+        .unwrap_or("nouid");
+    let task_id = code_run.spec.task_id;
+    let service_name = &code_run.spec.service;
+    let context_version = code_run.spec.context_version;
+
+    format!(
+        "code-{namespace}-{name}-{uid_suffix}-{service_name}-t{task_id}-v{context_version}-files"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::config::StorageOverride;
+
+    #[test]
+    fn test_sidecar_mounts_only_shared_volumes() {
+        let sidecar: Sidecar = serde_json::from_value(json!({
+            "name": "postgres",
+            "image": "postgres:16",
+            "args": ["-c", "fsync=off"],
+            "ports": [5432],
+            "env": { "POSTGRES_PASSWORD": "test" },
+            "sharedVolumes": ["workspace"]
+        }))
+        .unwrap();
+        let agent_mounts = [
+            json!({ "name": "task-files", "mountPath": "/task-files" }),
+            json!({ "name": "workspace", "mountPath": "/workspace" }),
+        ];
+
+        let container = sidecar_container(&sidecar, &agent_mounts);
+        assert_eq!(
+            container["volumeMounts"],
+            json!([{ "name": "workspace", "mountPath": "/workspace" }])
+        );
+        assert_eq!(container["ports"], json!([{ "containerPort": 5432 }]));
+        assert_eq!(
+            container["env"],
+            json!([{ "name": "POSTGRES_PASSWORD", "value": "test" }])
+        );
+        assert_eq!(container["args"], json!(["-c", "fsync=off"]));
+        assert_eq!(container["restartPolicy"], "Always");
+        assert!(container.get("command").is_none());
+    }
+
+    #[test]
+    fn test_workspace_pvc_uses_service_overrides() {
+        let mut storage = ControllerConfig::default().storage;
+        storage.storage_class_name = Some("local-path".to_string());
+        storage.service_overrides.insert(
+            "monorepo".to_string(),
+            StorageOverride {
+                storage_class_name: Some("fast-ssd".to_string()),
+                workspace_size: Some("50Gi".to_string()),
+            },
+        );
+        storage.service_overrides.insert(
+            "web".to_string(),
+            StorageOverride {
+                storage_class_name: None,
+                workspace_size: Some("20Gi".to_string()),
+            },
+        );
+
+        let spec_for = |service: &str| {
+            build_workspace_pvc(&format!("workspace-{service}"), service, &storage)
+                .spec
+                .unwrap()
+        };
+        let size = |spec: &k8s_openapi::api::core::v1::PersistentVolumeClaimSpec| {
+            spec.resources.as_ref().unwrap().requests.as_ref().unwrap()["storage"]
+                .0
+                .clone()
+        };
+
+        let monorepo = spec_for("monorepo");
+        assert_eq!(size(&monorepo), "50Gi");
+        assert_eq!(monorepo.storage_class_name.as_deref(), Some("fast-ssd"));
+
+        let web = spec_for("web");
+        assert_eq!(size(&web), "20Gi");
+        assert_eq!(web.storage_class_name.as_deref(), Some("local-path"));
+
+        let api = spec_for("api");
+        assert_eq!(size(&api), "10Gi");
+        assert_eq!(api.storage_class_name.as_deref(), Some("local-path"));
+        assert_eq!(api.access_modes, Some(vec!["ReadWriteOnce".to_string()]));
+    }
+
+    #[test]
+    fn test_quantity_bytes() {
+        assert_eq!(quantity_bytes("10Gi"), Some(10 << 30));
+        assert_eq!(quantity_bytes("500M"), Some(500_000_000));
+        assert_eq!(quantity_bytes("1024"), Some(1024));
+        assert!(quantity_bytes("5Gi") < quantity_bytes("10G"));
+        assert_eq!(quantity_bytes("1.5Gi"), None);
+    }
+
+    #[test]
+    fn test_format_previous_failure_keeps_log_tail() {
+        let short = format_previous_failure("run-1", "job-1", "cargo test\nerror[E0308]\n");
+        assert!(short.contains("```text\ncargo test\nerror[E0308]\n```"));
+        assert!(!short.contains("truncated"));
+
+        let long_log = format!("{}\nfinal error", "é".repeat(PREVIOUS_FAILURE_LOG_BYTES));
+        let long = format_previous_failure("run-1", "job-1", &long_log);
+        assert!(long.contains("truncated"));
+        assert!(long.contains("final error"));
+        assert!(long.len() < PREVIOUS_FAILURE_LOG_BYTES + 512);
+    }
+}
@@ -0,0 +1,160 @@
+//! Retention reaper for finished CodeRuns
+//!
+//! Keeps the most recent `keepLastSucceeded` / `keepLastFailed` CodeRuns per service and
+//! deletes older ones. Jobs and ConfigMaps are owned by the CodeRun, so they go with it.
+
+use crate::crds::CodeRun;
+use crate::tasks::config::CleanupConfig;
+use crate::tasks::types::{Context, Result};
+use kube::api::{Api, DeleteParams, ListParams, PropagationPolicy};
+use kube::ResourceExt;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// How often finished CodeRuns are checked against the retention counts
+const RETENTION_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Periodically delete finished CodeRuns beyond the configured retention
+///
+/// Returns immediately when cleanup is disabled or no retention count is configured.
+pub async fn run_retention_reaper(ctx: Arc<Context>) -> Result<()> {
+    let cleanup = &ctx.config.cleanup;
+    let has_retention = cleanup.keep_last_succeeded.is_some()
+        || cleanup.keep_last_failed.is_some()
+        || !cleanup.service_retention.is_empty();
+    if !cleanup.enabled || !has_retention {
+        info!("CodeRun retention reaper disabled (no retention counts configured)");
+        return Ok(());
+    }
+
+    info!(
+        "Starting CodeRun retention reaper (every {} minutes)",
+        RETENTION_INTERVAL.as_secs() / 60
+    );
+    let coderuns: Api<CodeRun> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
+    let mut interval = tokio::time::interval(RETENTION_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(e) = reap_once(&coderuns, cleanup).await {
+            warn!("CodeRun retention pass failed: {}", e);
+        }
+    }
+}
+
+async fn reap_once(coderuns: &Api<CodeRun>, cleanup: &CleanupConfig) -> Result<()> {
+    let runs = coderuns.list(&ListParams::default()).await?;
+    let expired = runs_beyond_retention(&runs.items, cleanup);
+    if expired.is_empty() {
+        debug!("No CodeRuns beyond retention");
+        return Ok(());
+    }
+
+    let delete_params = DeleteParams {
+        propagation_policy: Some(PropagationPolicy::Background),
+        ..Default::default()
+    };
+    for name in expired {
+        match coderuns.delete(&name, &delete_params).await {
+            Ok(_) => info!("Deleted CodeRun {} (beyond retention)", name),
+            Err(kube::Error::Api(ae)) if ae.code == 404 => {}
+            Err(e) => warn!("Failed to delete CodeRun {}: {}", name, e),
+        }
+    }
+    Ok(())
+}
+
+/// Names of finished CodeRuns to delete: per service and phase, everything after the newest
+/// `retention_for(service, phase)` runs by completion time
+fn runs_beyond_retention(runs: &[CodeRun], cleanup: &CleanupConfig) -> Vec<String> {
+    let mut groups: BTreeMap<(&str, &str), Vec<&CodeRun>> = BTreeMap::new();
+    for run in runs {
+        if run.metadata.deletion_timestamp.is_some() {
+            continue;
+        }
+        let Some(phase) = run.status.as_ref().map(|s| s.phase.as_str()) else {
+            continue;
+        };
+        if phase == "Succeeded" || phase == "Failed" {
+            groups
+                .entry((run.spec.service.as_str(), phase))
+                .or_default()
+                .push(run);
+        }
+    }
+
+    let mut expired = Vec::new();
+    for ((service, phase), mut group) in groups {
+        let Some(keep) = cleanup.retention_for(service, phase) else {
+            continue;
+        };
+        // Newest first
+        group.sort_by_key(|run| std::cmp::Reverse(completion_time(run)));
+        expired.extend(group.into_iter().skip(keep).map(ResourceExt::name_any));
+    }
+    expired
+}
+
+/// When a finished run completed: its last status update, else its creation time
+fn completion_time(run: &CodeRun) -> Option<chrono::DateTime<chrono::Utc>> {
+    run.status
+        .as_ref()
+        .and_then(|s| s.last_update.as_deref())
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&chrono::Utc))
+        .or_else(|| run.metadata.creation_timestamp.as_ref().map(|t| t.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::config::RetentionOverride;
+    use serde_json::json;
+
+    fn finished_run(name: &str, service: &str, phase: &str, finished: &str) -> CodeRun {
+        serde_json::from_value(json!({
+            "apiVersion": "agents.platform/v1",
+            "kind": "CodeRun",
+            "metadata": { "name": name },
+            "spec": {
+                "taskId": 1,
+                "service": service,
+                "repositoryUrl": "https://github.com/5dlabs/cto",
+                "docsRepositoryUrl": "https://github.com/5dlabs/cto",
+                "model": "claude-sonnet-4-20250514",
+                "githubApp": "5DLabs-Rex"
+            },
+            "status": { "phase": phase, "lastUpdate": finished }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_runs_beyond_retention_keeps_newest_per_service() {
+        let runs = vec![
+            finished_run("api-old", "api", "Succeeded", "2025-01-01T00:00:00Z"),
+            finished_run("api-new", "api", "Succeeded", "2025-01-03T00:00:00Z"),
+            finished_run("api-mid", "api", "Succeeded", "2025-01-02T00:00:00Z"),
+            finished_run("api-failed", "api", "Failed", "2025-01-01T00:00:00Z"),
+            finished_run("web-old", "web", "Succeeded", "2025-01-01T00:00:00Z"),
+            finished_run("web-new", "web", "Succeeded", "2025-01-02T00:00:00Z"),
+            finished_run("web-running", "web", "Running", "2025-01-01T00:00:00Z"),
+        ];
+        let cleanup = CleanupConfig {
+            keep_last_succeeded: Some(2),
+            service_retention: BTreeMap::from([(
+                "web".to_string(),
+                RetentionOverride {
+                    keep_last_succeeded: Some(1),
+                    keep_last_failed: None,
+                },
+            )]),
+            ..CleanupConfig::default()
+        };
+
+        let mut expired = runs_beyond_retention(&runs, &cleanup);
+        expired.sort();
+        assert_eq!(expired, vec!["api-old", "web-old"]);
+    }
+}
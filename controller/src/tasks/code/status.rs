@@ -57,7 +57,7 @@ impl CodeStatusManager {
         code_run: &Arc<CodeRun>,
         ctx: &Arc<Context>,
         job_name: &str,
-        _cm_name: &str,
+        cm_name: &str,
     ) -> Result<()> {
         let namespace = &ctx.namespace;
         let client = &ctx.client;
@@ -76,6 +76,7 @@ impl CodeStatusManager {
                 "message": "Code implementation job started",
                 "lastUpdate": chrono::Utc::now().to_rfc3339(),
                 "jobName": job_name,
+                "configmapName": cm_name,
                 "retryCount": current_retry_count,
                 "conditions": Self::build_conditions("Running", "Code implementation job started", &chrono::Utc::now().to_rfc3339())
             }
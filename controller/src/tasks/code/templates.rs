@@ -1,16 +1,12 @@
 use crate::crds::CodeRun;
 use crate::tasks::config::ControllerConfig;
+use crate::tasks::template_check;
 use crate::tasks::types::Result;
-use handlebars::Handlebars;
 use serde_json::json;
 use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
 use tracing::debug;
 
-// Template base path (mounted from ConfigMap)
-const CLAUDE_TEMPLATES_PATH: &str = "/claude-templates";
-
 pub struct CodeTemplateGenerator;
 
 impl CodeTemplateGenerator {
@@ -24,7 +20,7 @@ impl CodeTemplateGenerator {
         // Generate core code templates
         templates.insert(
             "container.sh".to_string(),
-            Self::generate_container_script(code_run)?,
+            Self::generate_container_script(code_run, config)?,
         );
         templates.insert(
             "CLAUDE.md".to_string(),
@@ -47,7 +43,13 @@ impl CodeTemplateGenerator {
         );
         templates.insert(
             "github-guidelines.md".to_string(),
-            Self::generate_github_guidelines(code_run)?,
+            Self::generate_github_guidelines(code_run, config)?,
+        );
+
+        // Shared script container.sh sources to trust the custom CA bundle
+        templates.insert(
+            "ca-trust.sh".to_string(),
+            Self::load_template("shared/ca-trust.sh")?,
         );
 
         // Generate hook scripts
@@ -60,9 +62,8 @@ impl CodeTemplateGenerator {
         Ok(templates)
     }
 
-    fn generate_container_script(code_run: &CodeRun) -> Result<String> {
-        let mut handlebars = Handlebars::new();
-        handlebars.set_strict_mode(false);
+    fn generate_container_script(code_run: &CodeRun, config: &ControllerConfig) -> Result<String> {
+        let mut handlebars = template_check::handlebars();
 
         let template = Self::load_template("code/container.sh.hbs")?;
 
@@ -82,10 +83,20 @@ impl CodeTemplateGenerator {
             "docs_branch": code_run.spec.docs_branch,
             "working_directory": Self::get_working_directory(code_run),
             "continue_session": Self::get_continue_session(code_run),
+            "attempts": Self::get_retry_count(code_run) + 1,
+            "user_requested": code_run.spec.continue_session,
             "overwrite_memory": code_run.spec.overwrite_memory,
             "docs_project_directory": code_run.spec.docs_project_directory.as_deref().unwrap_or(""),
             "github_app": code_run.spec.github_app.as_deref().unwrap_or(""),
+            "repository_visibility": code_run.spec.repository_visibility_hint().unwrap_or(""),
             "model": code_run.spec.model,
+            "init_commands": code_run.spec.init_commands,
+            "git_config": Self::get_git_config(code_run),
+            "base_branch": code_run.spec.base_branch.as_deref().map(shell_quote),
+            "feature_branch": shell_quote(&code_run.feature_branch(config.defaults.working_branch.as_deref())),
+            // Validated as hex, so safe to render unquoted
+            "commit_sha": code_run.spec.pinned_commit(),
+            "test_check_timeout_seconds": config.job.test_check_timeout_seconds,
         });
 
         handlebars
@@ -98,8 +109,7 @@ impl CodeTemplateGenerator {
     }
 
     fn generate_claude_memory(code_run: &CodeRun) -> Result<String> {
-        let mut handlebars = Handlebars::new();
-        handlebars.set_strict_mode(false);
+        let mut handlebars = template_check::handlebars();
 
         let template = Self::load_template("code/claude.md.hbs")?;
 
@@ -129,8 +139,7 @@ impl CodeTemplateGenerator {
     }
 
     fn generate_claude_settings(code_run: &CodeRun, config: &ControllerConfig) -> Result<String> {
-        let mut handlebars = Handlebars::new();
-        handlebars.set_strict_mode(false);
+        let mut handlebars = template_check::handlebars();
 
         let template = Self::load_template("code/settings.json.hbs")?;
 
@@ -142,27 +151,72 @@ impl CodeTemplateGenerator {
                 ))
             })?;
 
-        let context = json!({
+        handlebars
+            .render("claude_settings", &Self::settings_context(code_run, config))
+            .map_err(|e| {
+                crate::tasks::types::Error::ConfigError(format!(
+                    "Failed to render settings.json: {e}"
+                ))
+            })
+    }
+
+    fn settings_context(code_run: &CodeRun, config: &ControllerConfig) -> serde_json::Value {
+        let permissions = Self::build_agent_tools_permissions(code_run, config);
+        json!({
             "model": code_run.spec.model,
+            "agent_tools_override": permissions.is_some(),
+            "permissions": permissions,
+            "deny": Self::tool_deny_rules(code_run, config),
             "github_app": code_run.spec.github_app.as_deref().unwrap_or(""),
             "api_key_secret_name": config.secrets.api_key_secret_name,
             "api_key_secret_key": config.secrets.api_key_secret_key,
-            "working_directory": code_run.spec.working_directory.as_deref().unwrap_or(".")
-        });
-
-        handlebars.render("claude_settings", &context).map_err(|e| {
-            crate::tasks::types::Error::ConfigError(format!("Failed to render settings.json: {e}"))
+            "working_directory": code_run.spec.working_directory.as_deref().unwrap_or("."),
+            "telemetry": config.telemetry
         })
     }
 
+    /// Tool permissions for settings.json: the CodeRun's `toolsProfile`, else the configured
+    /// override, else `None` to keep the template's built-in list
+    fn build_agent_tools_permissions(
+        code_run: &CodeRun,
+        config: &ControllerConfig,
+    ) -> Option<serde_json::Value> {
+        Self::selected_permissions(code_run, config)
+            .map(|(allow, deny)| json!({ "allow": allow, "deny": deny }))
+    }
+
+    /// Deny rules written to settings.json: the selected permissions' deny list plus the
+    /// configured safe-mode rules
+    pub(crate) fn tool_deny_rules(code_run: &CodeRun, config: &ControllerConfig) -> Vec<String> {
+        let deny = Self::selected_permissions(code_run, config).map_or(&[][..], |(_, deny)| deny);
+        config.permissions.deny_with_safe_mode(deny)
+    }
+
+    /// Allow and deny lists from the CodeRun's `toolsProfile` or the configured override
+    fn selected_permissions<'a>(
+        code_run: &CodeRun,
+        config: &'a ControllerConfig,
+    ) -> Option<(&'a [String], &'a [String])> {
+        let permissions = &config.permissions;
+        if let Some(profile) = code_run
+            .spec
+            .selected_tools_profile()
+            .and_then(|name| permissions.profiles.get(name))
+        {
+            return Some((&profile.allow, &profile.deny));
+        }
+        permissions
+            .agent_tools_override
+            .then_some((&permissions.allow[..], &permissions.deny[..]))
+    }
+
     fn generate_mcp_config(_code_run: &CodeRun, _config: &ControllerConfig) -> Result<String> {
         // MCP config is currently static, so just load and return the template content
         Self::load_template("code/mcp.json.hbs")
     }
 
     fn generate_coding_guidelines(code_run: &CodeRun) -> Result<String> {
-        let mut handlebars = Handlebars::new();
-        handlebars.set_strict_mode(false);
+        let mut handlebars = template_check::handlebars();
 
         let template = Self::load_template("code/coding-guidelines.md.hbs")?;
 
@@ -188,9 +242,8 @@ impl CodeTemplateGenerator {
             })
     }
 
-    fn generate_github_guidelines(code_run: &CodeRun) -> Result<String> {
-        let mut handlebars = Handlebars::new();
-        handlebars.set_strict_mode(false);
+    fn generate_github_guidelines(code_run: &CodeRun, config: &ControllerConfig) -> Result<String> {
+        let mut handlebars = template_check::handlebars();
 
         let template = Self::load_template("code/github-guidelines.md.hbs")?;
 
@@ -204,8 +257,11 @@ impl CodeTemplateGenerator {
 
         let context = json!({
             "service": code_run.spec.service,
+            "repository_url": code_run.spec.repository_url,
             "working_directory": Self::get_working_directory(code_run),
             "github_app": code_run.spec.github_app.as_deref().unwrap_or(""),
+            "base_branch": code_run.spec.base_branch,
+            "feature_branch": code_run.feature_branch(config.defaults.working_branch.as_deref()),
         });
 
         handlebars
@@ -227,7 +283,7 @@ impl CodeTemplateGenerator {
         );
 
         // Read the ConfigMap directory and find files with the hook prefix
-        match std::fs::read_dir(CLAUDE_TEMPLATES_PATH) {
+        match std::fs::read_dir(template_check::templates_dir()) {
             Ok(entries) => {
                 for entry in entries.flatten() {
                     let path = entry.path();
@@ -246,12 +302,16 @@ impl CodeTemplateGenerator {
                                             hook_name, filename
                                         );
 
-                                        let mut handlebars = Handlebars::new();
-                                        handlebars.set_strict_mode(false);
+                                        let mut handlebars = template_check::handlebars();
 
                                         if let Err(e) = handlebars
                                             .register_template_string("hook", template_content)
                                         {
+                                            if template_check::is_checking() {
+                                                return Err(crate::tasks::types::Error::ConfigError(format!(
+                                                    "Failed to register hook template {hook_name}: {e}"
+                                                )));
+                                            }
                                             debug!(
                                                 "Failed to register hook template {}: {}",
                                                 hook_name, e
@@ -280,6 +340,11 @@ impl CodeTemplateGenerator {
                                                 );
                                             }
                                             Err(e) => {
+                                                if template_check::is_checking() {
+                                                    return Err(crate::tasks::types::Error::ConfigError(format!(
+                                                        "Failed to render code hook script {hook_name}: {e}"
+                                                    )));
+                                                }
                                                 debug!(
                                                     "Failed to render code hook script {}: {}",
                                                     hook_name, e
@@ -315,21 +380,34 @@ impl CodeTemplateGenerator {
         }
     }
 
+    /// Get custom git settings as shell-quoted pairs, sorted so renders are stable
+    fn get_git_config(code_run: &CodeRun) -> Vec<serde_json::Value> {
+        let settings: BTreeMap<_, _> = code_run.spec.git_config.iter().collect();
+        settings
+            .into_iter()
+            .map(|(key, value)| json!({ "key": shell_quote(key), "value": shell_quote(value) }))
+            .collect()
+    }
+
+    /// Get the number of retries recorded so far
+    fn get_retry_count(code_run: &CodeRun) -> u32 {
+        code_run
+            .status
+            .as_ref()
+            .map_or(0, |s| s.retry_count.unwrap_or(0))
+    }
+
     /// Get continue session flag - true for retries or user-requested continuation
     fn get_continue_session(code_run: &CodeRun) -> bool {
         // Continue if it's a retry attempt OR user explicitly requested it
-        let retry_count = code_run
-            .status
-            .as_ref()
-            .map_or(0, |s| s.retry_count.unwrap_or(0));
-        retry_count > 0 || code_run.spec.continue_session
+        Self::get_retry_count(code_run) > 0 || code_run.spec.continue_session
     }
 
     /// Load a template file from the mounted ConfigMap
     fn load_template(relative_path: &str) -> Result<String> {
         // Convert path separators to underscores for ConfigMap key lookup
         let configmap_key = relative_path.replace('/', "_");
-        let full_path = Path::new(CLAUDE_TEMPLATES_PATH).join(&configmap_key);
+        let full_path = template_check::templates_dir().join(&configmap_key);
         debug!(
             "Loading code template from: {} (key: {})",
             full_path.display(),
@@ -343,3 +421,124 @@ impl CodeTemplateGenerator {
         })
     }
 }
+
+/// Single-quote a value for safe interpolation into a shell script
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::config::ToolProfile;
+    use handlebars::Handlebars;
+
+    #[test]
+    fn test_tools_profile_overrides_configured_permissions() {
+        let mut config = ControllerConfig::default();
+        config.permissions.profiles.insert(
+            "readonly".to_string(),
+            ToolProfile {
+                allow: vec!["Read".to_string()],
+                deny: vec!["Bash".to_string()],
+            },
+        );
+        let code_run = |profile: serde_json::Value| -> CodeRun {
+            serde_json::from_value(json!({
+                "apiVersion": "agents.platform/v1",
+                "kind": "CodeRun",
+                "metadata": { "name": "example" },
+                "spec": {
+                    "taskId": 1,
+                    "service": "api",
+                    "repositoryUrl": "https://github.com/5dlabs/example",
+                    "docsRepositoryUrl": "https://github.com/5dlabs/docs",
+                    "model": "sonnet",
+                    "toolsProfile": profile
+                }
+            }))
+            .unwrap()
+        };
+
+        let permissions = CodeTemplateGenerator::build_agent_tools_permissions(
+            &code_run(json!("readonly")),
+            &config,
+        );
+        assert_eq!(
+            permissions,
+            Some(json!({ "allow": ["Read"], "deny": ["Bash"] }))
+        );
+
+        // Without a profile the template's built-in list applies unless the override is on
+        assert_eq!(
+            CodeTemplateGenerator::build_agent_tools_permissions(&code_run(json!(null)), &config),
+            None
+        );
+        config.permissions.agent_tools_override = true;
+        let permissions =
+            CodeTemplateGenerator::build_agent_tools_permissions(&code_run(json!(null)), &config);
+        assert_eq!(
+            permissions.unwrap()["allow"],
+            json!(config.permissions.allow)
+        );
+
+        // Safe-mode rules are appended to whichever deny list applies
+        config.permissions.safe_mode_deny = vec!["Bash(kubectl delete:*)".to_string()];
+        assert_eq!(
+            CodeTemplateGenerator::tool_deny_rules(&code_run(json!("readonly")), &config),
+            vec!["Bash", "Bash(kubectl delete:*)"]
+        );
+        config.permissions.safe_mode_deny.clear();
+        assert_eq!(
+            CodeTemplateGenerator::tool_deny_rules(&code_run(json!("readonly")), &config),
+            vec!["Bash"]
+        );
+    }
+
+    #[test]
+    fn test_settings_bound_telemetry_exports() {
+        let code_run: CodeRun = serde_json::from_value(json!({
+            "apiVersion": "agents.platform/v1",
+            "kind": "CodeRun",
+            "metadata": { "name": "test" },
+            "spec": {
+                "taskId": 1,
+                "service": "api",
+                "repositoryUrl": "https://github.com/5dlabs/example",
+                "docsRepositoryUrl": "https://github.com/5dlabs/docs",
+                "model": "sonnet",
+                "githubApp": "5DLabs-Rex"
+            }
+        }))
+        .unwrap();
+        let mut config = ControllerConfig::default();
+        config.telemetry.enabled = true;
+
+        let mut handlebars = Handlebars::new();
+        handlebars
+            .register_template_string(
+                "claude_settings",
+                include_str!(
+                    "../../../../infra/charts/controller/claude-templates/code/settings.json.hbs"
+                ),
+            )
+            .unwrap();
+        let rendered = handlebars
+            .render(
+                "claude_settings",
+                &CodeTemplateGenerator::settings_context(&code_run, &config),
+            )
+            .unwrap();
+        let settings: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+
+        let env = &settings["env"];
+        assert_eq!(env["CLAUDE_CODE_ENABLE_TELEMETRY"], "1");
+        for key in [
+            "OTEL_EXPORTER_OTLP_TIMEOUT",
+            "OTEL_METRIC_EXPORT_TIMEOUT",
+            "OTEL_BLRP_EXPORT_TIMEOUT",
+        ] {
+            assert_eq!(env[key], "5000", "{key}");
+        }
+    }
+}
@@ -6,6 +6,7 @@
 use k8s_openapi::api::core::v1::ConfigMap;
 use kube::{api::Api, Client};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Main controller configuration structure
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -31,6 +32,26 @@ pub struct ControllerConfig {
     /// Cleanup configuration
     #[serde(default)]
     pub cleanup: CleanupConfig,
+
+    /// Generated ConfigMap configuration
+    #[serde(default, rename = "configMap")]
+    pub configmap: ConfigMapConfig,
+
+    /// Reuse of recent successful DocsRuns with identical inputs
+    #[serde(default, rename = "docsReuse")]
+    pub docs_reuse: DocsReuseConfig,
+
+    /// Per-controller reconcile concurrency limits
+    #[serde(default)]
+    pub concurrency: ConcurrencyConfig,
+
+    /// Team that owns each GitHub App, used as the `owner` label for cost attribution
+    #[serde(default)]
+    pub owners: BTreeMap<String, String>,
+
+    /// Spec defaults the mutating admission webhook fills in when a run omits them
+    #[serde(default)]
+    pub defaults: SpecDefaultsConfig,
 }
 
 /// Job configuration
@@ -39,6 +60,50 @@ pub struct JobConfig {
     /// Job timeout in seconds
     #[serde(rename = "activeDeadlineSeconds")]
     pub active_deadline_seconds: i64,
+
+    /// Maximum number of Jobs a single CodeRun may start across all context versions
+    /// (unlimited when unset)
+    #[serde(rename = "maxTotalAttempts", default)]
+    pub max_total_attempts: Option<u32>,
+
+    /// Fail a CodeRun whose pod is unschedulable or can't pull its image for this long
+    /// (disabled when unset)
+    #[serde(rename = "startupTimeoutSeconds", default)]
+    pub startup_timeout_seconds: Option<u64>,
+
+    /// Copy the agent's latest output line into CodeRun `status.message` at most this often
+    /// (disabled when unset)
+    #[serde(rename = "progressIntervalSeconds", default)]
+    pub progress_interval_seconds: Option<u64>,
+
+    /// Retry a CodeRun or DocsRun reconcile that hit a transient Kubernetes API error
+    /// (conflict, throttling, server error) this many times, with exponential backoff
+    #[serde(
+        rename = "maxReconcileRetries",
+        default = "default_max_reconcile_retries"
+    )]
+    pub max_reconcile_retries: u32,
+
+    /// Wait this long after a CodeRun spec change before starting its Job, so rapid edits
+    /// (e.g. `contextVersion` bumped twice) produce one Job (disabled when unset)
+    #[serde(rename = "specSettleSeconds", default)]
+    pub spec_settle_seconds: Option<u64>,
+
+    /// Give up on the post-run test check after this long, so a slow suite can't push a
+    /// finished agent run into `activeDeadlineSeconds`
+    #[serde(
+        rename = "testCheckTimeoutSeconds",
+        default = "default_test_check_timeout_seconds"
+    )]
+    pub test_check_timeout_seconds: u64,
+}
+
+fn default_max_reconcile_retries() -> u32 {
+    5
+}
+
+fn default_test_check_timeout_seconds() -> u64 {
+    600
 }
 
 /// Agent configuration
@@ -50,6 +115,211 @@ pub struct AgentConfig {
     /// Image pull secrets for private registries
     #[serde(default, rename = "imagePullSecrets")]
     pub image_pull_secrets: Vec<String>,
+
+    /// Extra CA certificates the agent trusts, e.g. for a TLS-intercepting corporate proxy
+    #[serde(default, rename = "caBundle")]
+    pub ca_bundle: Option<CaBundleConfig>,
+
+    /// CPU/memory requests and limits for the agent container
+    #[serde(default)]
+    pub resources: AgentResourcesConfig,
+}
+
+/// Agent container resources per run type; docs runs default larger since they read whole repos.
+/// Each quantity left out of an override keeps its run type's default.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AgentResourcesConfig {
+    #[serde(
+        default = "default_code_resources",
+        deserialize_with = "deserialize_code_resources"
+    )]
+    pub code: ContainerResources,
+
+    #[serde(
+        default = "default_docs_resources",
+        deserialize_with = "deserialize_docs_resources"
+    )]
+    pub docs: ContainerResources,
+
+    #[serde(
+        default = "default_intake_resources",
+        deserialize_with = "deserialize_intake_resources"
+    )]
+    pub intake: ContainerResources,
+}
+
+impl Default for AgentResourcesConfig {
+    fn default() -> Self {
+        Self {
+            code: default_code_resources(),
+            docs: default_docs_resources(),
+            intake: default_intake_resources(),
+        }
+    }
+}
+
+/// Container `resources` block; serializes to the Kubernetes shape
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ContainerResources {
+    pub requests: ResourceQuantities,
+    pub limits: ResourceQuantities,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ResourceQuantities {
+    pub cpu: String,
+    pub memory: String,
+}
+
+impl ResourceQuantities {
+    fn new(cpu: &str, memory: &str) -> Self {
+        Self {
+            cpu: cpu.to_string(),
+            memory: memory.to_string(),
+        }
+    }
+}
+
+/// A `resources` override as written in the config; missing quantities fall back to a default
+#[derive(Debug, Default, Deserialize)]
+struct ContainerResourcesOverride {
+    #[serde(default)]
+    requests: ResourceQuantitiesOverride,
+    #[serde(default)]
+    limits: ResourceQuantitiesOverride,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ResourceQuantitiesOverride {
+    cpu: Option<String>,
+    memory: Option<String>,
+}
+
+impl ContainerResourcesOverride {
+    fn apply_to(self, defaults: ContainerResources) -> ContainerResources {
+        ContainerResources {
+            requests: self.requests.apply_to(defaults.requests),
+            limits: self.limits.apply_to(defaults.limits),
+        }
+    }
+}
+
+impl ResourceQuantitiesOverride {
+    fn apply_to(self, defaults: ResourceQuantities) -> ResourceQuantities {
+        ResourceQuantities {
+            cpu: self.cpu.unwrap_or(defaults.cpu),
+            memory: self.memory.unwrap_or(defaults.memory),
+        }
+    }
+}
+
+/// Deserialize a possibly partial `resources` block on top of `defaults`
+fn deserialize_resources_over<'de, D>(
+    deserializer: D,
+    defaults: ContainerResources,
+) -> Result<ContainerResources, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Option::<ContainerResourcesOverride>::deserialize(deserializer)
+        .map(|overrides| overrides.unwrap_or_default().apply_to(defaults))
+}
+
+fn deserialize_code_resources<'de, D>(deserializer: D) -> Result<ContainerResources, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserialize_resources_over(deserializer, default_code_resources())
+}
+
+fn deserialize_docs_resources<'de, D>(deserializer: D) -> Result<ContainerResources, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserialize_resources_over(deserializer, default_docs_resources())
+}
+
+fn deserialize_intake_resources<'de, D>(deserializer: D) -> Result<ContainerResources, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserialize_resources_over(deserializer, default_intake_resources())
+}
+
+fn default_code_resources() -> ContainerResources {
+    ContainerResources {
+        requests: ResourceQuantities::new("1", "2Gi"),
+        limits: ResourceQuantities::new("4", "8Gi"),
+    }
+}
+
+fn default_docs_resources() -> ContainerResources {
+    ContainerResources {
+        requests: ResourceQuantities::new("2", "4Gi"),
+        limits: ResourceQuantities::new("8", "16Gi"),
+    }
+}
+
+fn default_intake_resources() -> ContainerResources {
+    ContainerResources {
+        requests: ResourceQuantities::new("500m", "1Gi"),
+        limits: ResourceQuantities::new("2", "4Gi"),
+    }
+}
+
+/// Where the agent's extra CA bundle lives; exactly one of `configMapName` or `secretName`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CaBundleConfig {
+    /// ConfigMap holding the PEM bundle
+    #[serde(default, rename = "configMapName")]
+    pub config_map_name: Option<String>,
+
+    /// Secret holding the PEM bundle
+    #[serde(default, rename = "secretName")]
+    pub secret_name: Option<String>,
+
+    /// Key within the ConfigMap or Secret
+    #[serde(default = "default_ca_bundle_key")]
+    pub key: String,
+}
+
+fn default_ca_bundle_key() -> String {
+    "ca.crt".to_string()
+}
+
+impl CaBundleConfig {
+    /// Path the bundle is mounted at in agent containers, exported as `CUSTOM_CA_BUNDLE`
+    pub const MOUNT_PATH: &'static str = "/etc/ssl/custom-ca/ca.crt";
+
+    /// Name of the pod volume holding the bundle
+    pub const VOLUME_NAME: &'static str = "custom-ca";
+
+    /// Pod volume projecting the configured key to `ca.crt`
+    pub fn volume(&self) -> serde_json::Value {
+        let items = serde_json::json!([{ "key": self.key, "path": "ca.crt" }]);
+        match (&self.config_map_name, &self.secret_name) {
+            (Some(name), _) => serde_json::json!({
+                "name": Self::VOLUME_NAME,
+                "configMap": { "name": name, "items": items }
+            }),
+            (None, secret_name) => serde_json::json!({
+                "name": Self::VOLUME_NAME,
+                "secret": { "secretName": secret_name, "items": items }
+            }),
+        }
+    }
+
+    pub fn volume_mount(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": Self::VOLUME_NAME,
+            "mountPath": "/etc/ssl/custom-ca",
+            "readOnly": true
+        })
+    }
+
+    pub fn env_var(&self) -> serde_json::Value {
+        serde_json::json!({ "name": "CUSTOM_CA_BUNDLE", "value": Self::MOUNT_PATH })
+    }
 }
 
 /// Image configuration
@@ -86,6 +356,54 @@ pub struct PermissionsConfig {
 
     /// Denied tool patterns
     pub deny: Vec<String>,
+
+    /// Named tool presets a CodeRun can select with `toolsProfile`
+    #[serde(default)]
+    pub profiles: BTreeMap<String, ToolProfile>,
+
+    /// Destructive commands denied for every agent on top of any other deny rules
+    /// (set to an empty list to turn safe mode off)
+    #[serde(default = "default_safe_mode_deny", rename = "safeModeDeny")]
+    pub safe_mode_deny: Vec<String>,
+}
+
+fn default_safe_mode_deny() -> Vec<String> {
+    [
+        "Bash(rm -rf /)",
+        "Bash(rm -rf /*)",
+        "Bash(rm -rf ~)",
+        "Bash(git push --force:*)",
+        "Bash(git push -f:*)",
+        "Bash(kubectl delete:*)",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+impl PermissionsConfig {
+    /// `deny` followed by any safe-mode rules it doesn't already contain
+    pub fn deny_with_safe_mode(&self, deny: &[String]) -> Vec<String> {
+        let mut merged = deny.to_vec();
+        for rule in &self.safe_mode_deny {
+            if !merged.contains(rule) {
+                merged.push(rule.clone());
+            }
+        }
+        merged
+    }
+}
+
+/// A named set of tool permissions
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ToolProfile {
+    /// Allowed tool patterns
+    #[serde(default)]
+    pub allow: Vec<String>,
+
+    /// Denied tool patterns
+    #[serde(default)]
+    pub deny: Vec<String>,
 }
 
 /// Telemetry configuration (used in templates)
@@ -109,6 +427,17 @@ pub struct TelemetryConfig {
     /// Logs protocol (for code tasks)
     #[serde(rename = "logsProtocol")]
     pub logs_protocol: String,
+
+    /// Upper bound on each OTLP export, so an unreachable collector can't stall the agent
+    #[serde(
+        rename = "exportTimeoutMs",
+        default = "default_telemetry_export_timeout"
+    )]
+    pub export_timeout_ms: u64,
+}
+
+fn default_telemetry_export_timeout() -> u64 {
+    5000
 }
 
 /// Storage configuration
@@ -121,12 +450,53 @@ pub struct StorageConfig {
     /// Storage size for workspace PVCs
     #[serde(rename = "workspaceSize", default = "default_workspace_size")]
     pub workspace_size: String,
+
+    /// Access mode for new workspace PVCs: `ReadWriteOnce` or `ReadWriteMany` (for several
+    /// pods sharing a workspace across nodes)
+    #[serde(rename = "accessMode", default = "default_workspace_access_mode")]
+    pub access_mode: String,
+
+    /// Per-service overrides of `storageClassName` / `workspaceSize`, keyed by service name
+    #[serde(rename = "serviceOverrides", default)]
+    pub service_overrides: BTreeMap<String, StorageOverride>,
+}
+
+/// Workspace PVC settings for one service; unset fields fall back to the global settings
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct StorageOverride {
+    #[serde(rename = "storageClassName", default)]
+    pub storage_class_name: Option<String>,
+
+    #[serde(rename = "workspaceSize", default)]
+    pub workspace_size: Option<String>,
+}
+
+impl StorageConfig {
+    /// Storage class for `service`'s workspace PVC (`None` uses the cluster default)
+    pub fn storage_class_for(&self, service: &str) -> Option<&str> {
+        self.service_overrides
+            .get(service)
+            .and_then(|o| o.storage_class_name.as_deref())
+            .or(self.storage_class_name.as_deref())
+    }
+
+    /// Requested size of `service`'s workspace PVC
+    pub fn workspace_size_for(&self, service: &str) -> &str {
+        self.service_overrides
+            .get(service)
+            .and_then(|o| o.workspace_size.as_deref())
+            .unwrap_or(&self.workspace_size)
+    }
 }
 
 fn default_workspace_size() -> String {
     "10Gi".to_string()
 }
 
+fn default_workspace_access_mode() -> String {
+    "ReadWriteOnce".to_string()
+}
+
 /// Cleanup configuration
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct CleanupConfig {
@@ -148,6 +518,44 @@ pub struct CleanupConfig {
     /// Whether to delete the ConfigMap when cleaning up the job
     #[serde(rename = "deleteConfigMap", default = "default_delete_configmap")]
     pub delete_configmap: bool,
+
+    /// Succeeded CodeRuns kept per service; older ones are deleted (unset keeps all)
+    #[serde(rename = "keepLastSucceeded", default)]
+    pub keep_last_succeeded: Option<usize>,
+
+    /// Failed CodeRuns kept per service; older ones are deleted (unset keeps all)
+    #[serde(rename = "keepLastFailed", default)]
+    pub keep_last_failed: Option<usize>,
+
+    /// Per-service overrides of `keepLastSucceeded` / `keepLastFailed`, keyed by service name
+    #[serde(rename = "serviceRetention", default)]
+    pub service_retention: BTreeMap<String, RetentionOverride>,
+}
+
+/// Retention counts for one service; unset fields fall back to the global counts
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct RetentionOverride {
+    #[serde(rename = "keepLastSucceeded", default)]
+    pub keep_last_succeeded: Option<usize>,
+
+    #[serde(rename = "keepLastFailed", default)]
+    pub keep_last_failed: Option<usize>,
+}
+
+impl CleanupConfig {
+    /// How many finished runs in `phase` to keep for `service` (`None` keeps all)
+    pub fn retention_for(&self, service: &str, phase: &str) -> Option<usize> {
+        let overrides = self.service_retention.get(service);
+        match phase {
+            "Succeeded" => overrides
+                .and_then(|o| o.keep_last_succeeded)
+                .or(self.keep_last_succeeded),
+            "Failed" => overrides
+                .and_then(|o| o.keep_last_failed)
+                .or(self.keep_last_failed),
+            _ => None,
+        }
+    }
 }
 
 fn default_cleanup_enabled() -> bool {
@@ -173,10 +581,74 @@ impl Default for CleanupConfig {
             completed_job_delay_minutes: default_completed_delay(),
             failed_job_delay_minutes: default_failed_delay(),
             delete_configmap: default_delete_configmap(),
+            keep_last_succeeded: None,
+            keep_last_failed: None,
+            service_retention: BTreeMap::new(),
         }
     }
 }
 
+/// Generated ConfigMap configuration
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ConfigMapConfig {
+    /// Mark rendered task ConfigMaps as immutable so they can't be edited while a job runs
+    #[serde(default)]
+    pub immutable: bool,
+}
+
+/// DocsRun reuse configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DocsReuseConfig {
+    /// Short-circuit DocsRuns whose inputs match a recent Succeeded DocsRun
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// How long a Succeeded DocsRun can be reused for, in minutes
+    #[serde(rename = "ttlMinutes", default = "default_docs_reuse_ttl")]
+    pub ttl_minutes: u64,
+}
+
+fn default_docs_reuse_ttl() -> u64 {
+    1440 // 24 hours
+}
+
+impl Default for DocsReuseConfig {
+    fn default() -> Self {
+        DocsReuseConfig {
+            enabled: false,
+            ttl_minutes: default_docs_reuse_ttl(),
+        }
+    }
+}
+
+/// Reconcile concurrency limits, so a burst of one workload can't starve the other
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ConcurrencyConfig {
+    /// Maximum concurrent DocsRun reconciles (0 = unbounded)
+    #[serde(default)]
+    pub docs: u16,
+
+    /// Maximum concurrent CodeRun reconciles (0 = unbounded)
+    #[serde(default)]
+    pub code: u16,
+}
+
+/// Spec defaults applied at admission (unset leaves the field to the submitter)
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SpecDefaultsConfig {
+    /// Model for `CodeRun`s that don't set `spec.model`
+    #[serde(default, rename = "codeModel")]
+    pub code_model: Option<String>,
+
+    /// Model for `DocsRun`s that don't set `spec.model`
+    #[serde(default, rename = "docsModel")]
+    pub docs_model: Option<String>,
+
+    /// Branch name template for `CodeRun`s that don't set `spec.workingBranch`
+    #[serde(default, rename = "workingBranch")]
+    pub working_branch: Option<String>,
+}
+
 impl ControllerConfig {
     /// Validate that configuration has required fields
     pub fn validate(&self) -> Result<(), anyhow::Error> {
@@ -188,9 +660,32 @@ impl ControllerConfig {
                 Please ensure the 'agent.image.repository' and 'agent.image.tag' are set in the Helm values."
             ));
         }
+        if !matches!(
+            self.storage.access_mode.as_str(),
+            "ReadWriteOnce" | "ReadWriteMany"
+        ) {
+            return Err(anyhow::anyhow!(
+                "storage.accessMode must be ReadWriteOnce or ReadWriteMany, got '{}'",
+                self.storage.access_mode
+            ));
+        }
+        if let Some(ca_bundle) = &self.agent.ca_bundle {
+            if ca_bundle.config_map_name.is_some() == ca_bundle.secret_name.is_some() {
+                return Err(anyhow::anyhow!(
+                    "agent.caBundle must set exactly one of configMapName or secretName"
+                ));
+            }
+        }
         Ok(())
     }
 
+    /// Owning team for a run's GitHub App (`unknown` when the app isn't mapped)
+    pub fn owner_for(&self, github_app: Option<&str>) -> &str {
+        github_app
+            .and_then(|app| self.owners.get(app))
+            .map_or("unknown", String::as_str)
+    }
+
     /// Load configuration from mounted ConfigMap file
     pub fn from_mounted_file(config_path: &str) -> Result<Self, anyhow::Error> {
         let config_str = std::fs::read_to_string(config_path)
@@ -228,6 +723,12 @@ impl Default for ControllerConfig {
         Self {
             job: JobConfig {
                 active_deadline_seconds: 7200, // 2 hours
+                max_total_attempts: None,
+                startup_timeout_seconds: None,
+                progress_interval_seconds: None,
+                max_reconcile_retries: default_max_reconcile_retries(),
+                spec_settle_seconds: None,
+                test_check_timeout_seconds: default_test_check_timeout_seconds(),
             },
             agent: AgentConfig {
                 image: ImageConfig {
@@ -235,6 +736,8 @@ impl Default for ControllerConfig {
                     tag: "MISSING_IMAGE_CONFIG".to_string(),
                 },
                 image_pull_secrets: vec!["ghcr-secret".to_string()],
+                ca_bundle: None,
+                resources: AgentResourcesConfig::default(),
             },
             secrets: SecretsConfig {
                 api_key_secret_name: "orchestrator-secrets".to_string(),
@@ -255,6 +758,8 @@ impl Default for ControllerConfig {
                 deny: vec![
                     "Bash(npm:install*, yarn:install*, cargo:install*, docker:*, kubectl:*, rm:-rf*, git:*)".to_string(),
                 ],
+                profiles: BTreeMap::new(),
+                safe_mode_deny: default_safe_mode_deny(),
             },
             // Telemetry configuration with environment variable overrides:
             // - OTLP_ENDPOINT: OTLP traces endpoint (default: http://localhost:4317)
@@ -269,17 +774,28 @@ impl Default for ControllerConfig {
                     .unwrap_or_else(|_| "http://localhost:4318".to_string()),
                 logs_protocol: std::env::var("LOGS_PROTOCOL")
                     .unwrap_or_else(|_| "http".to_string()),
+                export_timeout_ms: default_telemetry_export_timeout(),
             },
             storage: StorageConfig {
                 storage_class_name: None, // Let K8s use default storage class
                 workspace_size: "10Gi".to_string(),
+                access_mode: default_workspace_access_mode(),
+                service_overrides: BTreeMap::new(),
             },
             cleanup: CleanupConfig {
                 enabled: true,
                 completed_job_delay_minutes: 5,
                 failed_job_delay_minutes: 60,
                 delete_configmap: true,
+                keep_last_succeeded: None,
+                keep_last_failed: None,
+                service_retention: BTreeMap::new(),
             },
+            configmap: ConfigMapConfig::default(),
+            docs_reuse: DocsReuseConfig::default(),
+            concurrency: ConcurrencyConfig::default(),
+            owners: BTreeMap::new(),
+            defaults: SpecDefaultsConfig::default(),
         }
     }
 }
@@ -298,6 +814,14 @@ agent:
   image:
     repository: "test/image"
     tag: "latest"
+  caBundle:
+    secretName: "corporate-ca"
+  resources:
+    code:
+      requests: { cpu: "500m", memory: "1Gi" }
+      limits: { cpu: "2", memory: "4Gi" }
+    docs:
+      limits: { memory: "32Gi" }
 
 secrets:
   apiKeySecretName: "test-secret"
@@ -318,22 +842,58 @@ telemetry:
 storage:
   storageClassName: "local-path"
   workspaceSize: "5Gi"
+  serviceOverrides:
+    monorepo:
+      workspaceSize: "50Gi"
 
 cleanup:
   enabled: true
   completedJobDelayMinutes: 5
   failedJobDelayMinutes: 60
   deleteConfigMap: true
+
+concurrency:
+  code: 10
+
+owners:
+  5DLabs-Rex: platform
 "#;
 
         let config: ControllerConfig = serde_yaml::from_str(yaml).unwrap();
         assert_eq!(config.job.active_deadline_seconds, 3600);
         assert_eq!(config.agent.image.repository, "test/image");
         assert!(config.telemetry.enabled);
+        assert_eq!(config.telemetry.export_timeout_ms, 5000);
         assert_eq!(config.permissions.allow, vec!["*"]);
         assert!(config.cleanup.enabled);
         assert_eq!(config.cleanup.completed_job_delay_minutes, 5);
         assert_eq!(config.cleanup.failed_job_delay_minutes, 60);
+        assert_eq!(config.concurrency.code, 10);
+        assert_eq!(config.concurrency.docs, 0);
+        assert_eq!(config.storage.access_mode, "ReadWriteOnce");
+        assert_eq!(config.storage.workspace_size_for("monorepo"), "50Gi");
+        assert_eq!(config.storage.workspace_size_for("api"), "5Gi");
+        assert_eq!(
+            config.storage.storage_class_for("monorepo"),
+            Some("local-path")
+        );
+        assert_eq!(config.owner_for(Some("5DLabs-Rex")), "platform");
+        assert_eq!(config.owner_for(Some("5DLabs-Blaze")), "unknown");
+        assert_eq!(config.owner_for(None), "unknown");
+
+        let ca_bundle = config.agent.ca_bundle.as_ref().unwrap();
+        assert_eq!(ca_bundle.key, "ca.crt");
+        assert_eq!(ca_bundle.volume()["secret"]["secretName"], "corporate-ca");
+        assert_eq!(ca_bundle.volume()["secret"]["items"][0]["path"], "ca.crt");
+        assert!(config.validate().is_ok());
+
+        let resources = &config.agent.resources;
+        assert_eq!(resources.code.requests.cpu, "500m");
+        assert_eq!(resources.code.limits.memory, "4Gi");
+        assert_eq!(resources.docs.requests, default_docs_resources().requests);
+        assert_eq!(resources.docs.limits.cpu, "8");
+        assert_eq!(resources.docs.limits.memory, "32Gi");
+        assert_eq!(resources.intake, default_intake_resources());
     }
 
     #[test]
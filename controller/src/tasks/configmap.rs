@@ -0,0 +1,211 @@
+//! Helpers shared by the DocsRun and CodeRun resource managers for the
+//! rendered task-files ConfigMaps.
+
+use crate::tasks::types::{fnv1a, resource_name};
+use k8s_openapi::api::{batch::v1::Job, core::v1::ConfigMap};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::OwnerReference;
+use std::collections::BTreeMap;
+
+/// Fingerprint of the rendered task files, so a run can be matched to the templates it used
+pub const CONTENT_HASH_ANNOTATION: &str = "agents.platform/content-hash";
+
+/// Version of the controller that rendered the task files
+pub const CONTROLLER_VERSION_ANNOTATION: &str = "agents.platform/controller-version";
+
+/// Stable hash of a ConfigMap's data (FNV-1a over each key and value)
+pub fn content_hash(data: &BTreeMap<String, String>) -> String {
+    let hash = fnv1a(data.iter().flat_map(|(key, value)| {
+        key.bytes()
+            .chain(std::iter::once(0))
+            .chain(value.bytes())
+            .chain(std::iter::once(0))
+    }));
+    format!("{hash:016x}")
+}
+
+/// Record the content hash and controller version on a rendered task ConfigMap
+pub fn annotate_content(configmap: &mut ConfigMap) {
+    let hash = content_hash(configmap.data.as_ref().unwrap_or(&BTreeMap::new()));
+    let annotations = configmap
+        .metadata
+        .annotations
+        .get_or_insert_with(BTreeMap::new);
+    annotations.insert(CONTENT_HASH_ANNOTATION.to_string(), hash);
+    annotations.insert(
+        CONTROLLER_VERSION_ANNOTATION.to_string(),
+        env!("CARGO_PKG_VERSION").to_string(),
+    );
+}
+
+/// Suffix an immutable task ConfigMap's name with its content hash and return the name
+///
+/// Immutable ConfigMaps can't be updated, and deleting and recreating one under the same
+/// name would change the files a running Job mounts. Naming it after its content sends
+/// changed templates to a new ConfigMap for the next Job instead; the old one goes with
+/// the Job that owns it. Mutable ConfigMaps keep their name.
+pub fn version_immutable_configmap(configmap: &mut ConfigMap) -> String {
+    let name = configmap.metadata.name.clone().unwrap_or_default();
+    if configmap.immutable != Some(true) {
+        return name;
+    }
+    let hash = content_hash(configmap.data.as_ref().unwrap_or(&BTreeMap::new()));
+    let versioned = resource_name(&format!("{name}-{}", &hash[..10]));
+    configmap.metadata.name = Some(versioned.clone());
+    versioned
+}
+
+/// How an existing task ConfigMap should be brought in line with freshly rendered templates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigMapUpdate {
+    /// Existing ConfigMap already holds the rendered content
+    Unchanged,
+    /// Mutable ConfigMap can be replaced in place
+    Replace,
+}
+
+/// Decide how to update `existing` so that it matches `desired`
+pub fn plan_configmap_update(existing: &ConfigMap, desired: &ConfigMap) -> ConfigMapUpdate {
+    // An immutable ConfigMap is named after its content, so one under that name already matches
+    if desired.immutable == Some(true) || existing.data == desired.data {
+        ConfigMapUpdate::Unchanged
+    } else {
+        ConfigMapUpdate::Replace
+    }
+}
+
+/// Name of the task-files ConfigMap a Job mounts
+pub fn job_task_configmap(job: &Job) -> Option<String> {
+    job.spec
+        .as_ref()?
+        .template
+        .spec
+        .as_ref()?
+        .volumes
+        .as_ref()?
+        .iter()
+        .find(|volume| volume.name == "task-files")?
+        .config_map
+        .as_ref()?
+        .name
+        .clone()
+}
+
+/// Owner reference that ties a task ConfigMap's lifetime to the Job mounting it
+pub fn job_owner_reference(job: &Job) -> Option<OwnerReference> {
+    Some(OwnerReference {
+        api_version: "batch/v1".to_string(),
+        kind: "Job".to_string(),
+        name: job.metadata.name.clone()?,
+        uid: job.metadata.uid.clone()?,
+        controller: Some(true),
+        block_owner_deletion: Some(true),
+    })
+}
+
+/// Add `owner` to the ConfigMap unless it's already there, so repeated reconciles (e.g. after
+/// a controller restart) don't stack duplicate references; returns whether it was added
+pub fn add_owner_reference(configmap: &mut ConfigMap, owner: OwnerReference) -> bool {
+    let owners = configmap
+        .metadata
+        .owner_references
+        .get_or_insert_with(Vec::new);
+    if owners.iter().any(|existing| existing.uid == owner.uid) {
+        return false;
+    }
+    owners.push(owner);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn configmap(immutable: Option<bool>, content: &str) -> ConfigMap {
+        let mut data = BTreeMap::new();
+        data.insert("container.sh".to_string(), content.to_string());
+        ConfigMap {
+            data: Some(data),
+            immutable,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_mutable_configmap_is_replaced() {
+        let existing = configmap(None, "old");
+        let desired = configmap(Some(false), "new");
+        assert_eq!(
+            plan_configmap_update(&existing, &desired),
+            ConfigMapUpdate::Replace
+        );
+    }
+
+    #[test]
+    fn test_immutable_configmap_is_versioned_by_content() {
+        let named = |immutable: Option<bool>, content: &str| {
+            let mut cm = configmap(immutable, content);
+            cm.metadata.name = Some("code-task-5-files".to_string());
+            cm
+        };
+
+        let mut mutable = named(None, "old");
+        assert_eq!(
+            version_immutable_configmap(&mut mutable),
+            "code-task-5-files"
+        );
+
+        let mut old = named(Some(true), "old");
+        let mut new = named(Some(true), "new");
+        let old_name = version_immutable_configmap(&mut old);
+        let new_name = version_immutable_configmap(&mut new);
+        assert!(old_name.starts_with("code-task-5-files-"), "{old_name}");
+        assert_ne!(old_name, new_name);
+        assert_eq!(new.metadata.name.as_deref(), Some(new_name.as_str()));
+        assert_eq!(
+            version_immutable_configmap(&mut named(Some(true), "old")),
+            old_name
+        );
+    }
+
+    #[test]
+    fn test_identical_immutable_configmap_is_reused() {
+        let existing = configmap(Some(true), "same");
+        let desired = configmap(Some(true), "same");
+        assert_eq!(
+            plan_configmap_update(&existing, &desired),
+            ConfigMapUpdate::Unchanged
+        );
+    }
+
+    #[test]
+    fn test_job_owner_reference_is_added_once() {
+        let job: Job = serde_json::from_value(serde_json::json!({
+            "metadata": { "name": "code-job-t1-v1", "uid": "1234-5678" }
+        }))
+        .unwrap();
+
+        let mut cm = configmap(None, "script");
+        let owner = job_owner_reference(&job).unwrap();
+        assert!(add_owner_reference(&mut cm, owner.clone()));
+        assert!(!add_owner_reference(&mut cm, owner));
+        assert_eq!(cm.metadata.owner_references.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_content_hash_tracks_rendered_files() {
+        let mut cm = configmap(None, "script");
+        annotate_content(&mut cm);
+        let annotations = cm.metadata.annotations.clone().unwrap();
+        assert_eq!(
+            annotations[CONTROLLER_VERSION_ANNOTATION],
+            env!("CARGO_PKG_VERSION")
+        );
+
+        let hash = &annotations[CONTENT_HASH_ANNOTATION];
+        assert_eq!(hash, &content_hash(cm.data.as_ref().unwrap()));
+        assert_ne!(
+            hash,
+            &content_hash(configmap(None, "new script").data.as_ref().unwrap())
+        );
+    }
+}
@@ -1,8 +1,14 @@
 use super::resources::DocsResourceManager;
-use crate::crds::DocsRun;
-use crate::tasks::types::{Context, Result, DOCS_FINALIZER_NAME};
-use k8s_openapi::api::{batch::v1::Job, core::v1::ConfigMap};
-use kube::api::{Patch, PatchParams};
+use crate::crds::{DocsRun, DocsRunSpec, PromptMode};
+use crate::tasks::types::{
+    fnv1a, job_needs_adoption, resolve_job_name, resource_name, Context, KubeResultExt, Result,
+    DOCS_FINALIZER_NAME,
+};
+use k8s_openapi::api::{
+    batch::v1::Job,
+    core::v1::{ConfigMap, Pod},
+};
+use kube::api::{ListParams, Patch, PatchParams};
 use kube::runtime::controller::Action;
 use kube::runtime::finalizer::{finalizer, Event as FinalizerEvent};
 use kube::{Api, ResourceExt};
@@ -25,6 +31,12 @@ pub async fn reconcile_docs_run(docs_run: Arc<DocsRun>, ctx: Arc<Context>) -> Re
     let docsruns: Api<DocsRun> = Api::namespaced(client.clone(), namespace);
     debug!("API clients created successfully");
 
+    // Paused runs are left untouched until un-paused (deletion still cleans up)
+    if docs_run.spec.paused && docs_run.metadata.deletion_timestamp.is_none() {
+        info!("DocsRun {} is paused, skipping reconciliation", name);
+        return Ok(Action::await_change());
+    }
+
     // Handle finalizers for cleanup
     let result = finalizer(
         &docsruns,
@@ -110,14 +122,38 @@ async fn reconcile_docs_create_or_update(docs_run: Arc<DocsRun>, ctx: &Context)
     // STEP 2: Check job state for running jobs
     let jobs: Api<Job> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
     let configmaps: Api<ConfigMap> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
-    let job_name = generate_job_name(&docs_run);
+    let job_name = resolve_job_name(&jobs, &docs_job_key(&docs_run), &ctx.namespace).await?;
     debug!("Generated job name: {}", job_name);
 
-    let job_state = check_job_state(&jobs, &job_name).await?;
+    let job_state = check_job_state(&jobs, &job_name, &ctx.namespace).await?;
     debug!("Current job state: {:?}", job_state);
 
     match job_state {
         JobState::NotFound => {
+            if let Err(message) = validate_docs_run_spec(&docs_run.spec) {
+                info!("DocsRun spec is invalid: {}", message);
+                update_docs_status_with_completion(&docs_run, ctx, "Failed", &message, false)
+                    .await?;
+                return Ok(Action::await_change());
+            }
+
+            let input_hash = docs_input_hash(&docs_run.spec);
+
+            // Reuse a recent identical run instead of paying for another job
+            if ctx.config.docs_reuse.enabled {
+                if let Some(prior) = find_reusable_docs_run(&docs_run, ctx, &input_hash).await? {
+                    reuse_docs_run_result(&docs_run, ctx, &prior, &input_hash).await?;
+                    return Ok(Action::await_change());
+                }
+            }
+
+            // Incremental runs diff tasks.json against the last successful run's commit
+            let docs_run = if docs_run.spec.changed_only {
+                resolve_changed_since(docs_run, ctx).await?
+            } else {
+                docs_run
+            };
+
             debug!("No existing job found, using optimistic job creation");
 
             // STEP 3: Optimistic job creation with conflict handling
@@ -130,6 +166,8 @@ async fn reconcile_docs_create_or_update(docs_run: Arc<DocsRun>, ctx: &Context)
                 .reconcile_create_or_update(&docs_run)
                 .await?;
 
+            record_docs_input_hash(&docs_run, ctx, &input_hash).await?;
+
             // Update status to Running
             update_docs_status_with_completion(
                 &docs_run,
@@ -147,6 +185,24 @@ async fn reconcile_docs_create_or_update(docs_run: Arc<DocsRun>, ctx: &Context)
         JobState::Running => {
             debug!("Job is still running, monitoring progress");
 
+            // A restart between creating the Job and recording it leaves status unaware of it
+            let recorded_job = docs_run.status.as_ref().and_then(|s| s.job_name.as_deref());
+            if job_needs_adoption(recorded_job, &job_name) {
+                if let Some(job) = jobs.get_opt(&job_name).await.for_resource(
+                    "get",
+                    "Job",
+                    &job_name,
+                    &ctx.namespace,
+                )? {
+                    let ctx_arc = Arc::new(ctx.clone());
+                    let resource_manager =
+                        DocsResourceManager::new(&jobs, &configmaps, &ctx.config, &ctx_arc);
+                    resource_manager.adopt_job(&docs_run, &job).await?;
+                    record_docs_input_hash(&docs_run, ctx, &docs_input_hash(&docs_run.spec))
+                        .await?;
+                }
+            }
+
             // Update status to Running if needed
             update_docs_status_with_completion(
                 &docs_run,
@@ -164,6 +220,11 @@ async fn reconcile_docs_create_or_update(docs_run: Arc<DocsRun>, ctx: &Context)
         JobState::Completed => {
             info!("Job completed successfully - marking work as complete");
 
+            // Record the generated-from commit as the marker for later incremental runs
+            if let Some(commit) = read_docs_source_commit(ctx, &job_name).await? {
+                record_docs_source_commit(&docs_run, ctx, &commit).await?;
+            }
+
             // Mark work as completed (TTL-safe)
             update_docs_status_with_completion(
                 &docs_run,
@@ -221,7 +282,12 @@ pub enum JobState {
     Failed,
 }
 
-fn generate_job_name(docs_run: &DocsRun) -> String {
+pub(super) fn generate_job_name(docs_run: &DocsRun) -> String {
+    resource_name(&docs_job_key(docs_run))
+}
+
+/// Job name for `docs_run` before it is made a valid, length-capped resource name
+fn docs_job_key(docs_run: &DocsRun) -> String {
     let namespace = docs_run.metadata.namespace.as_deref().unwrap_or("default");
     let name = docs_run.metadata.name.as_deref().unwrap_or("unknown");
     let uid_suffix = docs_run
@@ -232,11 +298,13 @@ fn generate_job_name(docs_run: &DocsRun) -> String {
         .unwrap_or("nouid");
 
     format!("docs-{namespace}-{name}-{uid_suffix}")
-        .replace(['_', '.'], "-")
-        .to_lowercase()
 }
 
-async fn check_job_state(jobs: &Api<Job>, job_name: &str) -> Result<JobState> {
+pub(crate) async fn check_job_state(
+    jobs: &Api<Job>,
+    job_name: &str,
+    namespace: &str,
+) -> Result<JobState> {
     match jobs.get(job_name).await {
         Ok(job) => {
             if let Some(status) = &job.status {
@@ -246,7 +314,7 @@ async fn check_job_state(jobs: &Api<Job>, job_name: &str) -> Result<JobState> {
             }
         }
         Err(kube::Error::Api(response)) if response.code == 404 => Ok(JobState::NotFound),
-        Err(e) => Err(e.into()),
+        Err(e) => Err(e).for_resource("get", "Job", job_name, namespace),
     }
 }
 
@@ -329,7 +397,13 @@ async fn update_docs_status_with_completion(
             &PatchParams::default(),
             &Patch::Merge(&status_patch),
         )
-        .await?;
+        .await
+        .for_resource(
+            "update status of",
+            "DocsRun",
+            &docs_run.name_any(),
+            &ctx.namespace,
+        )?;
 
     debug!(
         "Status updated successfully to '{}' with work_completed={}",
@@ -337,3 +411,407 @@ async fn update_docs_status_with_completion(
     );
     Ok(())
 }
+
+/// Check spec fields the CRD schema can't express before creating a Job
+fn validate_docs_run_spec(spec: &DocsRunSpec) -> std::result::Result<(), String> {
+    let paths = [
+        ("architectureFile", spec.architecture_file.as_deref()),
+        ("prdFile", spec.prd_file.as_deref()),
+    ];
+
+    let mut problems: Vec<String> = paths
+        .iter()
+        .filter_map(|(field, path)| path.map(|path| (field, path)))
+        .filter(|(_, path)| !is_repo_relative_path(path))
+        .map(|(field, path)| {
+            format!("spec.{field} '{path}' must be a relative path inside the repository")
+        })
+        .collect();
+
+    // Interpolated unquoted into the container script and hooks, so keep it to plain path characters
+    if let Some(dir) = spec
+        .taskmaster_dir
+        .as_deref()
+        .filter(|d| !d.trim().is_empty())
+    {
+        let plain = dir
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | '/'));
+        if !plain || !is_repo_relative_path(dir) {
+            problems.push(format!(
+                "spec.taskmasterDir '{dir}' must be a relative path of letters, digits, '.', '_', '-' and '/'"
+            ));
+        }
+    }
+
+    let has_guidance = spec
+        .prompt_modification
+        .as_deref()
+        .is_some_and(|m| !m.trim().is_empty());
+    if spec.prompt_mode == PromptMode::Replace && !has_guidance {
+        problems.push(
+            "spec.promptMode 'replace' needs a non-empty spec.promptModification".to_string(),
+        );
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems.join("; "))
+    }
+}
+
+fn is_repo_relative_path(path: &str) -> bool {
+    let path = std::path::Path::new(path);
+    !path.as_os_str().is_empty()
+        && path.components().all(|c| {
+            matches!(
+                c,
+                std::path::Component::Normal(_) | std::path::Component::CurDir
+            )
+        })
+}
+
+/// Fingerprint the spec fields that determine the generated documentation
+///
+/// The source tree itself isn't hashed (that would need a clone), so reuse is bounded
+/// by `docsReuse.ttlMinutes` to limit how far the branch can have moved on.
+fn docs_input_hash(spec: &DocsRunSpec) -> String {
+    let inputs = [
+        spec.repository_url.as_str(),
+        spec.source_branch.as_str(),
+        spec.working_directory.as_str(),
+        spec.model.as_deref().unwrap_or(""),
+        if spec.include_codebase.unwrap_or(false) {
+            "codebase"
+        } else {
+            ""
+        },
+        spec.output_format.extension(),
+        spec.architecture_file.as_deref().unwrap_or(""),
+        spec.prd_file.as_deref().unwrap_or(""),
+        spec.taskmaster_dir.as_deref().unwrap_or(""),
+        spec.prompt_mode.as_str(),
+        spec.prompt_modification.as_deref().unwrap_or(""),
+        if spec.changed_only {
+            "changed-only"
+        } else {
+            ""
+        },
+        spec.github_app.as_deref().unwrap_or(""),
+    ];
+
+    let hash = fnv1a(
+        inputs
+            .iter()
+            .flat_map(|input| input.bytes().chain(std::iter::once(0))),
+    );
+    format!("{hash:016x}")
+}
+
+/// Find the most recent Succeeded DocsRun with the same inputs that is still within the reuse TTL
+async fn find_reusable_docs_run(
+    docs_run: &DocsRun,
+    ctx: &Context,
+    input_hash: &str,
+) -> Result<Option<DocsRun>> {
+    let docsruns: Api<DocsRun> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
+    let ttl = chrono::Duration::minutes(ctx.config.docs_reuse.ttl_minutes as i64);
+    let now = chrono::Utc::now();
+    let name = docs_run.name_any();
+
+    let candidate = docsruns
+        .list(&ListParams::default())
+        .await?
+        .into_iter()
+        .filter(|other| other.name_any() != name)
+        .filter_map(|other| {
+            let status = other.status.as_ref()?;
+            // Only reuse runs that actually generated docs, so the TTL can't be chained
+            if status.phase != "Succeeded"
+                || status.reused_from.is_some()
+                || status.input_hash.as_deref() != Some(input_hash)
+            {
+                return None;
+            }
+            let finished = chrono::DateTime::parse_from_rfc3339(status.last_update.as_deref()?)
+                .ok()?
+                .with_timezone(&chrono::Utc);
+            (now - finished <= ttl).then_some((finished, other))
+        })
+        .max_by_key(|(finished, _)| *finished)
+        .map(|(_, other)| other);
+
+    Ok(candidate)
+}
+
+/// Mark a DocsRun Succeeded by pointing at the result of an earlier identical run
+async fn reuse_docs_run_result(
+    docs_run: &DocsRun,
+    ctx: &Context,
+    prior: &DocsRun,
+    input_hash: &str,
+) -> Result<()> {
+    let docsruns: Api<DocsRun> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
+    let prior_name = prior.name_any();
+    let pull_request_url = prior
+        .status
+        .as_ref()
+        .and_then(|s| s.pull_request_url.clone());
+
+    let message = match &pull_request_url {
+        Some(url) => format!("Inputs unchanged since DocsRun {prior_name}, reusing {url}"),
+        None => format!("Inputs unchanged since DocsRun {prior_name}, reusing its result"),
+    };
+    info!("{}", message);
+
+    let status_patch = json!({
+        "status": {
+            "phase": "Succeeded",
+            "message": message,
+            "lastUpdate": chrono::Utc::now().to_rfc3339(),
+            "workCompleted": true,
+            "inputHash": input_hash,
+            "reusedFrom": prior_name,
+            "pullRequestUrl": pull_request_url,
+        }
+    });
+
+    docsruns
+        .patch_status(
+            &docs_run.name_any(),
+            &PatchParams::default(),
+            &Patch::Merge(&status_patch),
+        )
+        .await
+        .for_resource(
+            "update status of",
+            "DocsRun",
+            &docs_run.name_any(),
+            &ctx.namespace,
+        )?;
+    Ok(())
+}
+
+/// Record the input fingerprint so later DocsRuns can reuse this run's result
+async fn record_docs_input_hash(docs_run: &DocsRun, ctx: &Context, input_hash: &str) -> Result<()> {
+    let docsruns: Api<DocsRun> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
+
+    let status_patch = json!({
+        "status": {
+            "inputHash": input_hash,
+        }
+    });
+
+    docsruns
+        .patch_status(
+            &docs_run.name_any(),
+            &PatchParams::default(),
+            &Patch::Merge(&status_patch),
+        )
+        .await
+        .for_resource(
+            "update status of",
+            "DocsRun",
+            &docs_run.name_any(),
+            &ctx.namespace,
+        )?;
+    Ok(())
+}
+
+/// Point an incremental DocsRun at the commit of the last successful run for the same project
+async fn resolve_changed_since(docs_run: Arc<DocsRun>, ctx: &Context) -> Result<Arc<DocsRun>> {
+    let docsruns: Api<DocsRun> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
+    let name = docs_run.name_any();
+    let spec = &docs_run.spec;
+
+    let marker = docsruns
+        .list(&ListParams::default())
+        .await?
+        .into_iter()
+        .filter(|other| {
+            other.name_any() != name
+                && other.spec.repository_url == spec.repository_url
+                && other.spec.source_branch == spec.source_branch
+                && other.spec.working_directory == spec.working_directory
+        })
+        .filter_map(|other| {
+            let status = other.status?;
+            if status.phase != "Succeeded" {
+                return None;
+            }
+            Some((
+                status.last_update.unwrap_or_default(),
+                status.source_commit?,
+            ))
+        })
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, commit)| commit);
+
+    let Some(commit) = marker else {
+        info!("No earlier successful DocsRun for this project, generating docs for all tasks");
+        return Ok(docs_run);
+    };
+
+    info!("Regenerating only tasks changed since {}", commit);
+    let status_patch = json!({
+        "status": {
+            "changedSince": commit,
+        }
+    });
+    let updated = docsruns
+        .patch_status(&name, &PatchParams::default(), &Patch::Merge(&status_patch))
+        .await
+        .for_resource("update status of", "DocsRun", &name, &ctx.namespace)?;
+    Ok(Arc::new(updated))
+}
+
+/// Read the commit the docs job reported through its termination message
+async fn read_docs_source_commit(ctx: &Context, job_name: &str) -> Result<Option<String>> {
+    let pods: Api<Pod> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
+    let job_pods = pods
+        .list(&ListParams::default().labels(&format!("job-name={job_name}")))
+        .await?;
+
+    let commit = job_pods
+        .iter()
+        .filter_map(|pod| pod.status.as_ref()?.container_statuses.as_ref())
+        .flatten()
+        .filter_map(|c| c.state.as_ref()?.terminated.as_ref()?.message.as_deref())
+        .find_map(parse_source_commit);
+    Ok(commit)
+}
+
+/// Extract `sourceCommit=<sha>` from a container termination message
+fn parse_source_commit(message: &str) -> Option<String> {
+    message
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("sourceCommit="))
+        .find(|sha| sha.len() == 40 && sha.chars().all(|c| c.is_ascii_hexdigit()))
+        .map(str::to_string)
+}
+
+/// Record the commit a successful DocsRun generated documentation from
+async fn record_docs_source_commit(docs_run: &DocsRun, ctx: &Context, commit: &str) -> Result<()> {
+    let docsruns: Api<DocsRun> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
+
+    let status_patch = json!({
+        "status": {
+            "sourceCommit": commit,
+        }
+    });
+
+    docsruns
+        .patch_status(
+            &docs_run.name_any(),
+            &PatchParams::default(),
+            &Patch::Merge(&status_patch),
+        )
+        .await
+        .for_resource(
+            "update status of",
+            "DocsRun",
+            &docs_run.name_any(),
+            &ctx.namespace,
+        )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(source_branch: &str) -> DocsRunSpec {
+        serde_json::from_value(json!({
+            "repositoryUrl": "https://github.com/5dlabs/example",
+            "workingDirectory": "_projects/example",
+            "sourceBranch": source_branch,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_docs_input_hash_tracks_inputs() {
+        assert_eq!(
+            docs_input_hash(&spec("main")),
+            docs_input_hash(&spec("main"))
+        );
+        assert_ne!(
+            docs_input_hash(&spec("main")),
+            docs_input_hash(&spec("develop"))
+        );
+
+        let mut rst = spec("main");
+        rst.output_format = crate::crds::DocsOutputFormat::Rst;
+        assert_ne!(docs_input_hash(&spec("main")), docs_input_hash(&rst));
+
+        // A retry with guidance must not reuse the run it is correcting
+        let mut retry = spec("main");
+        retry.prompt_modification = Some("The last run missed task 7".to_string());
+        assert_ne!(docs_input_hash(&spec("main")), docs_input_hash(&retry));
+
+        // An incremental run documents less than a full one, and another app commits as someone else
+        let mut changed_only = spec("main");
+        changed_only.changed_only = true;
+        assert_ne!(
+            docs_input_hash(&spec("main")),
+            docs_input_hash(&changed_only)
+        );
+        let mut other_app = spec("main");
+        other_app.github_app = Some("5DLabs-Blaze".to_string());
+        assert_ne!(docs_input_hash(&spec("main")), docs_input_hash(&other_app));
+    }
+
+    #[test]
+    fn test_replace_prompt_mode_needs_guidance() {
+        let mut replace = spec("main");
+        replace.prompt_mode = PromptMode::Replace;
+        assert!(validate_docs_run_spec(&replace).is_err());
+        replace.prompt_modification = Some("Only document task 7".to_string());
+        assert!(validate_docs_run_spec(&replace).is_ok());
+    }
+
+    #[test]
+    fn test_source_file_overrides_must_be_repo_relative() {
+        let mut overridden = spec("main");
+        overridden.architecture_file = Some("docs/design/architecture.md".to_string());
+        overridden.prd_file = Some("./requirements/prd.md".to_string());
+        assert!(validate_docs_run_spec(&overridden).is_ok());
+
+        overridden.architecture_file = Some("/etc/passwd".to_string());
+        overridden.prd_file = Some("../other-repo/prd.md".to_string());
+        assert_eq!(
+            validate_docs_run_spec(&overridden).unwrap_err(),
+            "spec.architectureFile '/etc/passwd' must be a relative path inside the repository; \
+             spec.prdFile '../other-repo/prd.md' must be a relative path inside the repository"
+        );
+    }
+
+    #[test]
+    fn test_taskmaster_dir_must_be_a_plain_relative_path() {
+        let mut nested = spec("main");
+        for dir in ["", "services/api/.taskmaster", "./.taskmaster/"] {
+            nested.taskmaster_dir = Some(dir.to_string());
+            assert!(validate_docs_run_spec(&nested).is_ok(), "{dir}");
+        }
+        for dir in [
+            "../.taskmaster",
+            "/srv/.taskmaster",
+            "api/$(id)/.taskmaster",
+        ] {
+            nested.taskmaster_dir = Some(dir.to_string());
+            assert!(validate_docs_run_spec(&nested).is_err(), "{dir}");
+        }
+    }
+
+    #[test]
+    fn test_parse_source_commit() {
+        let sha = "0123456789abcdef0123456789abcdef01234567";
+        assert_eq!(
+            parse_source_commit(&format!("sourceCommit={sha}\n")),
+            Some(sha.to_string())
+        );
+        assert_eq!(parse_source_commit("sourceCommit=not-a-sha"), None);
+        assert_eq!(parse_source_commit("Error: exit 1"), None);
+    }
+}
@@ -1,6 +1,14 @@
+use super::controller::generate_job_name;
 use crate::crds::DocsRun;
 use crate::tasks::config::ControllerConfig;
-use crate::tasks::types::{github_app_secret_name, ssh_secret_name, Context, Result};
+use crate::tasks::configmap::{
+    add_owner_reference, annotate_content, job_owner_reference, job_task_configmap,
+    plan_configmap_update, version_immutable_configmap, ConfigMapUpdate,
+};
+use crate::tasks::types::{
+    build_resource, github_app_secret_name, resource_name, ssh_secret_name, Context, KubeResultExt,
+    Result,
+};
 use k8s_openapi::api::{batch::v1::Job, core::v1::ConfigMap};
 use k8s_openapi::apimachinery::pkg::apis::meta::v1::{ObjectMeta, OwnerReference};
 use kube::api::{Api, DeleteParams, ListParams, PostParams};
@@ -9,7 +17,7 @@ use kube::ResourceExt;
 use serde_json::json;
 use std::collections::BTreeMap;
 use std::sync::Arc;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 pub struct DocsResourceManager<'a> {
     pub jobs: &'a Api<Job>,
@@ -48,8 +56,9 @@ impl<'a> DocsResourceManager<'a> {
         info!("📝 RESOURCE_MANAGER: Generated ConfigMap name: {}", cm_name);
 
         info!("🏗️ RESOURCE_MANAGER: Creating ConfigMap object");
-        let configmap = match self.create_configmap(docs_run, &cm_name, None) {
-            Ok(cm) => {
+        let mut configmap = match self.create_configmap(docs_run, &cm_name, None) {
+            Ok(mut cm) => {
+                annotate_content(&mut cm);
                 info!("✅ RESOURCE_MANAGER: ConfigMap object created successfully");
                 cm
             }
@@ -66,6 +75,8 @@ impl<'a> DocsResourceManager<'a> {
             }
         };
 
+        let cm_name = version_immutable_configmap(&mut configmap);
+
         // Always create or update ConfigMap to ensure latest template content
         info!(
             "🔄 RESOURCE_MANAGER: Attempting to create ConfigMap: {}",
@@ -92,31 +103,39 @@ impl<'a> DocsResourceManager<'a> {
 
                 // First get the existing ConfigMap to preserve resourceVersion
                 match self.configmaps.get(&cm_name).await {
-                    Ok(existing_cm) => {
-                        let mut updated_configmap = configmap;
-                        updated_configmap.metadata.resource_version =
-                            existing_cm.metadata.resource_version;
-
-                        match self
-                            .configmaps
-                            .replace(&cm_name, &PostParams::default(), &updated_configmap)
-                            .await
-                        {
-                            Ok(_) => {
-                                error!("✅ RESOURCE_MANAGER: Successfully updated existing ConfigMap: {}", cm_name);
-                            }
-                            Err(e) => {
-                                error!("❌ RESOURCE_MANAGER: Failed to replace existing ConfigMap {}: {:?}", cm_name, e);
-                                error!(
-                                    "❌ RESOURCE_MANAGER: Replace error type: {}",
-                                    std::any::type_name_of_val(&e)
-                                );
-
-                                // Fall back to creating a new one with a different name
-                                error!("🔄 RESOURCE_MANAGER: Replace failed, falling back to create-only approach");
+                    Ok(existing_cm) => match plan_configmap_update(&existing_cm, &configmap) {
+                        ConfigMapUpdate::Unchanged => {
+                            error!(
+                                "✅ RESOURCE_MANAGER: ConfigMap {} already up to date",
+                                cm_name
+                            );
+                        }
+                        ConfigMapUpdate::Replace => {
+                            let mut updated_configmap = configmap;
+                            updated_configmap.metadata.resource_version =
+                                existing_cm.metadata.resource_version;
+
+                            match self
+                                .configmaps
+                                .replace(&cm_name, &PostParams::default(), &updated_configmap)
+                                .await
+                            {
+                                Ok(_) => {
+                                    error!("✅ RESOURCE_MANAGER: Successfully updated existing ConfigMap: {}", cm_name);
+                                }
+                                Err(e) => {
+                                    error!("❌ RESOURCE_MANAGER: Failed to replace existing ConfigMap {}: {:?}", cm_name, e);
+                                    error!(
+                                        "❌ RESOURCE_MANAGER: Replace error type: {}",
+                                        std::any::type_name_of_val(&e)
+                                    );
+
+                                    // Fall back to creating a new one with a different name
+                                    error!("🔄 RESOURCE_MANAGER: Replace failed, falling back to create-only approach");
+                                }
                             }
                         }
-                    }
+                    },
                     Err(e) => {
                         error!("❌ RESOURCE_MANAGER: Failed to get existing ConfigMap {} for update: {:?}", cm_name, e);
                         error!(
@@ -134,7 +153,7 @@ impl<'a> DocsResourceManager<'a> {
                     "❌ RESOURCE_MANAGER: Kubernetes error type: {}",
                     std::any::type_name_of_val(&e)
                 );
-                return Err(e.into());
+                return Err(e).for_resource("create", "ConfigMap", &cm_name, &self.ctx.namespace);
             }
         }
 
@@ -150,6 +169,37 @@ impl<'a> DocsResourceManager<'a> {
         Ok(Action::await_change())
     }
 
+    /// Take over a Job created before a controller restart that status never recorded:
+    /// record it as started and give its ConfigMap the owner reference
+    pub async fn adopt_job(&self, docs_run: &Arc<DocsRun>, job: &Job) -> Result<()> {
+        let job_name = job.name_any();
+        // The Job names the ConfigMap it mounts, whether versioned or under a legacy name
+        let cm_name =
+            job_task_configmap(job).unwrap_or_else(|| self.generate_configmap_name(docs_run));
+        info!("Adopting existing docs job {} after restart", job_name);
+
+        if let Some(owner_ref) = job_owner_reference(job) {
+            match self
+                .update_configmap_owner(docs_run, &cm_name, owner_ref)
+                .await
+            {
+                Ok(()) => {}
+                Err(e) if e.api_status() == Some(404) => {
+                    warn!(
+                        "ConfigMap {} for adopted job {} is missing",
+                        cm_name, job_name
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        super::status::DocsStatusManager::update_job_started(
+            docs_run, self.ctx, &job_name, &cm_name,
+        )
+        .await
+    }
+
     pub async fn cleanup_resources(&self, docs_run: &Arc<DocsRun>) -> Result<Action> {
         let name = docs_run.name_any();
         info!("Cleaning up docs resources for: {}", name);
@@ -162,21 +212,7 @@ impl<'a> DocsResourceManager<'a> {
     }
 
     fn generate_configmap_name(&self, docs_run: &DocsRun) -> String {
-        // Generate unique ConfigMap name per DocsRun to prevent conflicts between sequential jobs
-        let namespace = docs_run.metadata.namespace.as_deref().unwrap_or("default");
-        let name = docs_run.metadata.name.as_deref().unwrap_or("unknown");
-        let uid_suffix = docs_run
-            .metadata
-            .uid
-            .as_deref()
-            .map(|uid| &uid[..8]) // Use first 8 chars of UID for uniqueness
-            .unwrap_or("nouid");
-        let context_version = 1; // Docs don't have context versions, always 1
-
-        // Use deterministic naming based on DocsRun UID for stable references
-        format!("docs-{namespace}-{name}-{uid_suffix}-v{context_version}-files")
-            .replace(['_', '.'], "-")
-            .to_lowercase()
+        resource_name(&configmap_key(docs_run))
     }
 
     fn create_configmap(
@@ -247,6 +283,7 @@ impl<'a> DocsResourceManager<'a> {
         let configmap = ConfigMap {
             metadata,
             data: Some(data),
+            immutable: self.config.configmap.immutable.then_some(true),
             ..Default::default()
         };
 
@@ -260,7 +297,7 @@ impl<'a> DocsResourceManager<'a> {
         docs_run: &DocsRun,
         cm_name: &str,
     ) -> Result<Option<OwnerReference>> {
-        let job_name = self.generate_job_name(docs_run);
+        let job_name = generate_job_name(docs_run);
 
         // FIRST: Check if the job already exists
         match self.jobs.get(&job_name).await {
@@ -332,7 +369,7 @@ impl<'a> DocsResourceManager<'a> {
                 );
                 Ok(owner_ref)
             }
-            Err(crate::tasks::types::Error::KubeError(kube::Error::Api(ae))) if ae.code == 409 => {
+            Err(e) if e.api_status() == Some(409) => {
                 // Job was created by another reconciliation loop, get the existing one
                 error!("🔄 RESOURCE_MANAGER: Job {} was created concurrently (409 conflict), getting existing job", job_name);
                 match self.jobs.get(&job_name).await {
@@ -349,7 +386,7 @@ impl<'a> DocsResourceManager<'a> {
                     }
                     Err(e) => {
                         error!("❌ RESOURCE_MANAGER: Failed to get existing job after 409 conflict: {:?}", e);
-                        Err(e.into())
+                        Err(e).for_resource("get", "Job", &job_name, &self.ctx.namespace)
                     }
                 }
             }
@@ -368,14 +405,18 @@ impl<'a> DocsResourceManager<'a> {
         docs_run: &DocsRun,
         cm_name: &str,
     ) -> Result<Option<OwnerReference>> {
-        let job_name = self.generate_job_name(docs_run);
+        let job_name = generate_job_name(docs_run);
 
         // Ensure PVC exists before creating job
         self.ensure_workspace_pvc(docs_run).await?;
 
         let job = self.build_job_spec(docs_run, &job_name, cm_name)?;
 
-        let created_job = self.jobs.create(&PostParams::default(), &job).await?;
+        let created_job = self
+            .jobs
+            .create(&PostParams::default(), &job)
+            .await
+            .for_resource("create", "Job", &job_name, &self.ctx.namespace)?;
 
         error!("✅ RESOURCE_MANAGER: Created docs job: {}", job_name);
 
@@ -411,23 +452,6 @@ impl<'a> DocsResourceManager<'a> {
         }
     }
 
-    fn generate_job_name(&self, docs_run: &DocsRun) -> String {
-        // Use deterministic naming based on the DocsRun's actual name and UID
-        // This ensures the same DocsRun always generates the same Job name
-        let namespace = docs_run.metadata.namespace.as_deref().unwrap_or("default");
-        let name = docs_run.metadata.name.as_deref().unwrap_or("unknown");
-        let uid_suffix = docs_run
-            .metadata
-            .uid
-            .as_deref()
-            .map(|uid| &uid[..8]) // Use first 8 chars of UID for uniqueness
-            .unwrap_or("nouid");
-
-        format!("docs-{namespace}-{name}-{uid_suffix}")
-            .replace(['_', '.'], "-")
-            .to_lowercase()
-    }
-
     fn build_job_spec(&self, docs_run: &DocsRun, job_name: &str, cm_name: &str) -> Result<Job> {
         let labels = self.create_task_labels(docs_run);
 
@@ -511,6 +535,49 @@ impl<'a> DocsResourceManager<'a> {
         volumes.extend(ssh_volumes.volumes);
         volume_mounts.extend(ssh_volumes.volume_mounts);
 
+        let github_identity = docs_run
+            .spec
+            .github_app
+            .as_deref()
+            .or(docs_run.spec.github_user.as_deref())
+            .unwrap_or("");
+        let mut env_vars = vec![
+            json!({
+                "name": "GITHUB_APP_PRIVATE_KEY",
+                "valueFrom": {
+                    "secretKeyRef": {
+                        "name": github_app_secret_name(github_identity),
+                        "key": "private-key"
+                    }
+                }
+            }),
+            json!({
+                "name": "GITHUB_APP_ID",
+                "valueFrom": {
+                    "secretKeyRef": {
+                        "name": github_app_secret_name(github_identity),
+                        "key": "app-id"
+                    }
+                }
+            }),
+            json!({
+                "name": "ANTHROPIC_API_KEY",
+                "valueFrom": {
+                    "secretKeyRef": {
+                        "name": self.config.secrets.api_key_secret_name,
+                        "key": self.config.secrets.api_key_secret_key
+                    }
+                }
+            }),
+        ];
+
+        // Extra CA certificates for TLS-intercepting proxies; container.sh installs them
+        if let Some(ca_bundle) = &self.config.agent.ca_bundle {
+            volumes.push(ca_bundle.volume());
+            volume_mounts.push(ca_bundle.volume_mount());
+            env_vars.push(ca_bundle.env_var());
+        }
+
         let image = format!(
             "{}:{}",
             self.config.agent.image.repository, self.config.agent.image.tag
@@ -542,43 +609,12 @@ impl<'a> DocsResourceManager<'a> {
                         "containers": [{
                             "name": "claude-docs",
                             "image": image,
-                            "env": [
-                                {
-                                    "name": "GITHUB_APP_PRIVATE_KEY",
-                                    "valueFrom": {
-                                        "secretKeyRef": {
-                                            "name": github_app_secret_name(docs_run.spec.github_app.as_deref()
-                                                .or(docs_run.spec.github_user.as_deref())
-                                                .unwrap_or("")),
-                                            "key": "private-key"
-                                        }
-                                    }
-                                },
-                                {
-                                    "name": "GITHUB_APP_ID",
-                                    "valueFrom": {
-                                        "secretKeyRef": {
-                                            "name": github_app_secret_name(docs_run.spec.github_app.as_deref()
-                                                .or(docs_run.spec.github_user.as_deref())
-                                                .unwrap_or("")),
-                                            "key": "app-id"
-                                        }
-                                    }
-                                },
-                                {
-                                    "name": "ANTHROPIC_API_KEY",
-                                    "valueFrom": {
-                                        "secretKeyRef": {
-                                            "name": self.config.secrets.api_key_secret_name,
-                                            "key": self.config.secrets.api_key_secret_key
-                                        }
-                                    }
-                                }
-                            ],
+                            "env": env_vars,
                             "command": ["/bin/bash"],
                             "args": ["/task-files/container.sh"],
                             "workingDir": "/workspace",
-                            "volumeMounts": volume_mounts
+                            "volumeMounts": volume_mounts,
+                            "resources": self.config.agent.resources.docs
                         }],
                         "volumes": volumes
                     }
@@ -586,7 +622,7 @@ impl<'a> DocsResourceManager<'a> {
             }
         });
 
-        Ok(serde_json::from_value(job_spec)?)
+        build_resource("Job", job_spec)
     }
 
     fn create_task_labels(&self, docs_run: &DocsRun) -> BTreeMap<String, String> {
@@ -617,6 +653,10 @@ impl<'a> DocsResourceManager<'a> {
             self.sanitize_label_value(github_identity),
         );
         labels.insert("context-version".to_string(), "1".to_string()); // Docs always version 1
+        labels.insert(
+            "owner".to_string(),
+            self.sanitize_label_value(self.config.owner_for(docs_run.spec.github_app.as_deref())),
+        );
 
         // Docs-specific labels
         labels.insert("task-type".to_string(), "docs".to_string());
@@ -670,19 +710,23 @@ impl<'a> DocsResourceManager<'a> {
         cm_name: &str,
         owner_ref: OwnerReference,
     ) -> Result<()> {
-        let mut existing_cm = self.configmaps.get(cm_name).await?;
+        let mut existing_cm = self.configmaps.get(cm_name).await.for_resource(
+            "get",
+            "ConfigMap",
+            cm_name,
+            &self.ctx.namespace,
+        )?;
 
-        // Add owner reference
-        let owner_refs = existing_cm
-            .metadata
-            .owner_references
-            .get_or_insert_with(Vec::new);
-        owner_refs.push(owner_ref);
+        if !add_owner_reference(&mut existing_cm, owner_ref) {
+            info!("ConfigMap {} already owned by its Job", cm_name);
+            return Ok(());
+        }
 
         // Update the ConfigMap
         self.configmaps
             .replace(cm_name, &PostParams::default(), &existing_cm)
-            .await?;
+            .await
+            .for_resource("replace", "ConfigMap", cm_name, &self.ctx.namespace)?;
         info!("Updated ConfigMap {} with owner reference", cm_name);
 
         Ok(())
@@ -835,7 +879,14 @@ impl<'a> DocsResourceManager<'a> {
                 // PVC doesn't exist, create it
                 error!("📦 Creating PVC: {}", pvc_name);
             }
-            Err(e) => return Err(e.into()),
+            Err(e) => {
+                return Err(e).for_resource(
+                    "get",
+                    "PersistentVolumeClaim",
+                    &pvc_name,
+                    &self.ctx.namespace,
+                )
+            }
         }
 
         // Create PVC
@@ -856,7 +907,7 @@ impl<'a> DocsResourceManager<'a> {
                 ..Default::default()
             },
             spec: Some(k8s_openapi::api::core::v1::PersistentVolumeClaimSpec {
-                access_modes: Some(vec!["ReadWriteOnce".to_string()]),
+                access_modes: Some(vec![self.config.storage.access_mode.clone()]),
                 resources: Some(k8s_openapi::api::core::v1::VolumeResourceRequirements {
                     requests: Some({
                         let mut requests = std::collections::BTreeMap::new();
@@ -887,7 +938,12 @@ impl<'a> DocsResourceManager<'a> {
             }
             Err(e) => {
                 error!("❌ Failed to create PVC {}: {:?}", pvc_name, e);
-                Err(e.into())
+                Err(e).for_resource(
+                    "create",
+                    "PersistentVolumeClaim",
+                    &pvc_name,
+                    &self.ctx.namespace,
+                )
             }
         }
     }
@@ -897,3 +953,20 @@ struct SshVolumes {
     volumes: Vec<serde_json::Value>,
     volume_mounts: Vec<serde_json::Value>,
 }
+
+/// ConfigMap name for `docs_run` before it is made a valid, length-capped resource name
+fn configmap_key(docs_run: &DocsRun) -> String {
+    // Generate unique ConfigMap name per DocsRun to prevent conflicts between sequential jobs
+    let namespace = docs_run.metadata.namespace.as_deref().unwrap_or("default");
+    let name = docs_run.metadata.name.as_deref().unwrap_or("unknown");
+    let uid_suffix = docs_run
+        .metadata
+        .uid
+        .as_deref()
+        .map(|uid| &uid[..8]) // Use first 8 chars of UID for uniqueness
+        .unwrap_or("nouid");
+    let context_version = 1; // Docs don't have context versions, always 1
+
+    // Use deterministic naming based on DocsRun UID for stable references
+    format!("docs-{namespace}-{name}-{uid_suffix}-v{context_version}-files")
+}
@@ -67,7 +67,7 @@ impl DocsStatusManager {
         docs_run: &Arc<DocsRun>,
         ctx: &Arc<Context>,
         job_name: &str,
-        _cm_name: &str,
+        cm_name: &str,
     ) -> Result<()> {
         let namespace = &ctx.namespace;
         let client = &ctx.client;
@@ -81,6 +81,7 @@ impl DocsStatusManager {
                 "message": "Documentation generation job started",
                 "lastUpdate": chrono::Utc::now().to_rfc3339(),
                 "jobName": job_name,
+                "configmapName": cm_name,
                 "conditions": Self::build_conditions("Running", "Documentation generation job started", &chrono::Utc::now().to_rfc3339())
             }
         });
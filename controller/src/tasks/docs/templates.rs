@@ -1,16 +1,13 @@
-use crate::crds::DocsRun;
+use crate::crds::{DocsRun, DocsRunSpec, PromptMode};
 use crate::tasks::config::ControllerConfig;
+use crate::tasks::template_check;
 use crate::tasks::types::Result;
 use handlebars::Handlebars;
 use serde_json::json;
 use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
 use tracing::debug;
 
-// Template base path (mounted from ConfigMap)
-const CLAUDE_TEMPLATES_PATH: &str = "/claude-templates";
-
 pub struct DocsTemplateGenerator;
 
 impl DocsTemplateGenerator {
@@ -39,6 +36,12 @@ impl DocsTemplateGenerator {
             Self::generate_docs_prompt(docs_run)?,
         );
 
+        // Shared script container.sh sources to trust the custom CA bundle
+        templates.insert(
+            "ca-trust.sh".to_string(),
+            Self::load_template("shared/ca-trust.sh")?,
+        );
+
         // Generate hook scripts
         let hook_scripts = Self::generate_hook_scripts(docs_run)?;
         for (filename, content) in hook_scripts {
@@ -50,8 +53,7 @@ impl DocsTemplateGenerator {
     }
 
     fn generate_container_script(docs_run: &DocsRun) -> Result<String> {
-        let mut handlebars = Handlebars::new();
-        handlebars.set_strict_mode(false);
+        let mut handlebars = template_check::handlebars();
 
         let template = Self::load_template("docs/container.sh.hbs")?;
 
@@ -70,7 +72,9 @@ impl DocsTemplateGenerator {
             "github_app": docs_run.spec.github_app.as_deref().unwrap_or(""),
             "model": docs_run.spec.model.as_deref().unwrap_or(""),
             "service_name": "docs-generator",
-            "include_codebase": docs_run.spec.include_codebase.unwrap_or(false)
+            "include_codebase": docs_run.spec.include_codebase.unwrap_or(false),
+            "changed_since": Self::get_changed_since(docs_run),
+            "taskmaster_dir": Self::get_taskmaster_dir(docs_run)
         });
 
         handlebars
@@ -83,8 +87,7 @@ impl DocsTemplateGenerator {
     }
 
     fn generate_claude_memory(docs_run: &DocsRun) -> Result<String> {
-        let mut handlebars = Handlebars::new();
-        handlebars.set_strict_mode(false);
+        let mut handlebars = template_check::handlebars();
 
         let template = Self::load_template("docs/claude.md.hbs")?;
 
@@ -102,7 +105,12 @@ impl DocsTemplateGenerator {
             "working_directory": docs_run.spec.working_directory,
             "github_app": docs_run.spec.github_app.as_deref().unwrap_or(""),
             "model": docs_run.spec.model.as_deref().unwrap_or(""),
-            "service_name": "docs-generator"
+            "service_name": "docs-generator",
+            "output_format": docs_run.spec.output_format.display_name(),
+            "doc_ext": docs_run.spec.output_format.extension(),
+            "architecture_file": Self::get_architecture_file(docs_run),
+            "prd_file": Self::get_prd_file(docs_run),
+            "taskmaster_dir": Self::get_taskmaster_dir(docs_run)
         });
 
         handlebars.render("claude_memory", &context).map_err(|e| {
@@ -111,8 +119,7 @@ impl DocsTemplateGenerator {
     }
 
     fn generate_claude_settings(docs_run: &DocsRun, config: &ControllerConfig) -> Result<String> {
-        let mut handlebars = Handlebars::new();
-        handlebars.set_strict_mode(false);
+        let mut handlebars = template_check::handlebars();
 
         let template = Self::load_template("docs/settings.json.hbs")?;
 
@@ -140,7 +147,9 @@ impl DocsTemplateGenerator {
             "github_app": docs_run.spec.github_app.as_deref().unwrap_or(""),
             "api_key_secret_name": config.secrets.api_key_secret_name,
             "api_key_secret_key": config.secrets.api_key_secret_key,
-            "working_directory": &docs_run.spec.working_directory
+            "working_directory": &docs_run.spec.working_directory,
+            "deny": config.permissions.safe_mode_deny,
+            "telemetry": config.telemetry
         });
 
         handlebars.render("claude_settings", &context).map_err(|e| {
@@ -149,8 +158,7 @@ impl DocsTemplateGenerator {
     }
 
     fn generate_docs_prompt(docs_run: &DocsRun) -> Result<String> {
-        let mut handlebars = Handlebars::new();
-        handlebars.set_strict_mode(false);
+        let mut handlebars = template_check::handlebars();
 
         let template = Self::load_template("docs/prompt.md.hbs")?;
 
@@ -172,12 +180,40 @@ impl DocsTemplateGenerator {
             "working_directory": docs_run.spec.working_directory,
             "service_name": "docs-generator",
             "toolman_catalog_markdown": catalog_markdown,
-            "include_codebase": docs_run.spec.include_codebase.unwrap_or(false)
+            "include_codebase": docs_run.spec.include_codebase.unwrap_or(false),
+            "output_format": docs_run.spec.output_format.display_name(),
+            "doc_ext": docs_run.spec.output_format.extension(),
+            "changed_since": Self::get_changed_since(docs_run),
+            "architecture_file": Self::get_architecture_file(docs_run),
+            "prd_file": Self::get_prd_file(docs_run),
+            "taskmaster_dir": Self::get_taskmaster_dir(docs_run)
         });
 
-        handlebars.render("docs_prompt", &context).map_err(|e| {
+        let prompt = handlebars.render("docs_prompt", &context).map_err(|e| {
             crate::tasks::types::Error::ConfigError(format!("Failed to render docs prompt: {e}"))
-        })
+        })?;
+
+        Ok(Self::apply_prompt_modification(prompt, &docs_run.spec))
+    }
+
+    /// Fold retry guidance from `promptModification` into the rendered prompt
+    fn apply_prompt_modification(prompt: String, spec: &DocsRunSpec) -> String {
+        let Some(guidance) = spec
+            .prompt_modification
+            .as_deref()
+            .map(str::trim)
+            .filter(|g| !g.is_empty())
+        else {
+            return prompt;
+        };
+
+        match spec.prompt_mode {
+            PromptMode::Append => format!(
+                "{}\n\n## Additional Guidance\n\nThis is a retry of an earlier documentation run. Follow this guidance, which takes precedence over the instructions above:\n\n{guidance}\n",
+                prompt.trim_end()
+            ),
+            PromptMode::Replace => format!("{guidance}\n"),
+        }
     }
 
     // Removed generate_toolman_catalog - catalog is now embedded as markdown in prompt
@@ -232,8 +268,7 @@ impl DocsTemplateGenerator {
     }
 
     fn render_toolman_catalog_markdown(catalog_data: &serde_json::Value) -> Result<String> {
-        let mut handlebars = Handlebars::new();
-        handlebars.set_strict_mode(false);
+        let mut handlebars = template_check::handlebars();
 
         // Register json helper for proper JSON serialization
         handlebars.register_helper(
@@ -296,7 +331,7 @@ impl DocsTemplateGenerator {
         );
 
         // Read the ConfigMap directory and find files with the hook prefix
-        match std::fs::read_dir(CLAUDE_TEMPLATES_PATH) {
+        match std::fs::read_dir(template_check::templates_dir()) {
             Ok(entries) => {
                 for entry in entries.flatten() {
                     let path = entry.path();
@@ -315,12 +350,16 @@ impl DocsTemplateGenerator {
                                             hook_name, filename
                                         );
 
-                                        let mut handlebars = Handlebars::new();
-                                        handlebars.set_strict_mode(false);
+                                        let mut handlebars = template_check::handlebars();
 
                                         if let Err(e) = handlebars
                                             .register_template_string("hook", template_content)
                                         {
+                                            if template_check::is_checking() {
+                                                return Err(crate::tasks::types::Error::ConfigError(format!(
+                                                    "Failed to register hook template {hook_name}: {e}"
+                                                )));
+                                            }
                                             debug!(
                                                 "Failed to register hook template {}: {}",
                                                 hook_name, e
@@ -333,7 +372,9 @@ impl DocsTemplateGenerator {
                                             "source_branch": docs_run.spec.source_branch,
                                             "working_directory": docs_run.spec.working_directory,
                                             "github_app": docs_run.spec.github_app.as_deref().unwrap_or(""),
-                                            "service_name": "docs-generator"
+                                            "service_name": "docs-generator",
+                                            "doc_ext": docs_run.spec.output_format.extension(),
+                                            "taskmaster_dir": Self::get_taskmaster_dir(docs_run)
                                         });
 
                                         match handlebars.render("hook", &context) {
@@ -348,6 +389,11 @@ impl DocsTemplateGenerator {
                                                 );
                                             }
                                             Err(e) => {
+                                                if template_check::is_checking() {
+                                                    return Err(crate::tasks::types::Error::ConfigError(format!(
+                                                        "Failed to render docs hook script {hook_name}: {e}"
+                                                    )));
+                                                }
                                                 debug!(
                                                     "Failed to render docs hook script {}: {}",
                                                     hook_name, e
@@ -375,11 +421,53 @@ impl DocsTemplateGenerator {
         Ok(hook_scripts)
     }
 
+    /// Marker commit for incremental runs (empty when regenerating everything)
+    fn get_changed_since(docs_run: &DocsRun) -> &str {
+        docs_run
+            .status
+            .as_ref()
+            .and_then(|s| s.changed_since.as_deref())
+            .filter(|_| docs_run.spec.changed_only)
+            .unwrap_or("")
+    }
+
+    /// Task Master directory relative to the working directory Claude runs in
+    fn get_taskmaster_dir(docs_run: &DocsRun) -> &str {
+        docs_run
+            .spec
+            .taskmaster_dir
+            .as_deref()
+            .map(|dir| dir.trim().trim_end_matches('/'))
+            .filter(|dir| !dir.is_empty())
+            .unwrap_or(".taskmaster")
+    }
+
+    /// Architecture doc path as seen from the working directory Claude runs in
+    fn get_architecture_file(docs_run: &DocsRun) -> String {
+        docs_run.spec.architecture_file.as_deref().map_or_else(
+            || {
+                format!(
+                    "{}/docs/architecture.md",
+                    Self::get_taskmaster_dir(docs_run)
+                )
+            },
+            |path| format!("/workspace/{path}"),
+        )
+    }
+
+    /// PRD path as seen from the working directory Claude runs in
+    fn get_prd_file(docs_run: &DocsRun) -> String {
+        docs_run.spec.prd_file.as_deref().map_or_else(
+            || format!("{}/docs/prd.txt", Self::get_taskmaster_dir(docs_run)),
+            |path| format!("/workspace/{path}"),
+        )
+    }
+
     /// Load a template file from the mounted ConfigMap
     fn load_template(relative_path: &str) -> Result<String> {
         // Convert path separators to underscores for ConfigMap key lookup
         let configmap_key = relative_path.replace('/', "_");
-        let full_path = Path::new(CLAUDE_TEMPLATES_PATH).join(&configmap_key);
+        let full_path = template_check::templates_dir().join(&configmap_key);
         debug!(
             "Loading docs template from: {} (key: {})",
             full_path.display(),
@@ -393,3 +481,43 @@ impl DocsTemplateGenerator {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(prompt_mode: &str, prompt_modification: Option<&str>) -> DocsRunSpec {
+        serde_json::from_value(json!({
+            "repositoryUrl": "https://github.com/5dlabs/example",
+            "workingDirectory": "_projects/example",
+            "sourceBranch": "main",
+            "promptMode": prompt_mode,
+            "promptModification": prompt_modification,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_apply_prompt_modification() {
+        let prompt = "Generate docs.\n".to_string();
+        assert_eq!(
+            DocsTemplateGenerator::apply_prompt_modification(prompt.clone(), &spec("append", None)),
+            prompt
+        );
+
+        let appended = DocsTemplateGenerator::apply_prompt_modification(
+            prompt.clone(),
+            &spec("append", Some("The last run missed task 7")),
+        );
+        assert!(appended.starts_with("Generate docs.\n\n## Additional Guidance"));
+        assert!(appended.ends_with("The last run missed task 7\n"));
+
+        assert_eq!(
+            DocsTemplateGenerator::apply_prompt_modification(
+                prompt,
+                &spec("replace", Some("Only document task 7"))
+            ),
+            "Only document task 7\n"
+        );
+    }
+}
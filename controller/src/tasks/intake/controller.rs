@@ -0,0 +1,225 @@
+use super::resources::{generate_configmap_name, generate_job_name, IntakeResourceManager};
+use crate::crds::IntakeRun;
+use crate::tasks::docs::controller::{check_job_state, JobState};
+use crate::tasks::types::{Context, KubeResultExt, Result, INTAKE_FINALIZER_NAME};
+use k8s_openapi::api::{batch::v1::Job, core::v1::ConfigMap};
+use kube::api::{Patch, PatchParams};
+use kube::runtime::controller::Action;
+use kube::runtime::finalizer::{finalizer, Event as FinalizerEvent};
+use kube::{Api, ResourceExt};
+use serde_json::json;
+use std::sync::Arc;
+use tracing::{debug, info, instrument};
+
+#[instrument(skip(ctx), fields(intake_run_name = %intake_run.name_any(), namespace = %ctx.namespace))]
+pub async fn reconcile_intake_run(intake_run: Arc<IntakeRun>, ctx: Arc<Context>) -> Result<Action> {
+    info!(
+        "Starting reconcile for IntakeRun: {}",
+        intake_run.name_any()
+    );
+
+    let intakeruns: Api<IntakeRun> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
+
+    finalizer(
+        &intakeruns,
+        INTAKE_FINALIZER_NAME,
+        intake_run,
+        |event| async {
+            match event {
+                FinalizerEvent::Apply(run) => reconcile_intake_create_or_update(run, &ctx).await,
+                // Job and ConfigMap are owned by the IntakeRun and garbage-collected with it
+                FinalizerEvent::Cleanup(_) => Ok(Action::await_change()),
+            }
+        },
+    )
+    .await
+    .map_err(|e| match e {
+        kube::runtime::finalizer::Error::ApplyFailed(err) => err,
+        kube::runtime::finalizer::Error::CleanupFailed(err) => err,
+        kube::runtime::finalizer::Error::AddFinalizer(e) => {
+            crate::tasks::types::Error::KubeError(e)
+        }
+        kube::runtime::finalizer::Error::RemoveFinalizer(e) => {
+            crate::tasks::types::Error::KubeError(e)
+        }
+        kube::runtime::finalizer::Error::UnnamedObject => {
+            crate::tasks::types::Error::MissingObjectKey
+        }
+        kube::runtime::finalizer::Error::InvalidFinalizer => {
+            crate::tasks::types::Error::ConfigError("Invalid finalizer name".to_string())
+        }
+    })
+}
+
+async fn reconcile_intake_create_or_update(
+    intake_run: Arc<IntakeRun>,
+    ctx: &Context,
+) -> Result<Action> {
+    if let Some(status) = &intake_run.status {
+        if status.work_completed == Some(true) || status.phase == "Failed" {
+            debug!(
+                "IntakeRun already finished ({}), nothing to do",
+                status.phase
+            );
+            return Ok(Action::await_change());
+        }
+    }
+
+    let jobs: Api<Job> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
+    let configmaps: Api<ConfigMap> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
+    let job_name = generate_job_name(&intake_run);
+
+    match check_job_state(&jobs, &job_name, &ctx.namespace).await? {
+        JobState::NotFound => {
+            if let Err(message) = validate_intake_run(&intake_run) {
+                info!("IntakeRun spec is invalid: {}", message);
+                update_intake_status(&intake_run, ctx, "Failed", &message, None).await?;
+                return Ok(Action::await_change());
+            }
+
+            let ctx_arc = Arc::new(ctx.clone());
+            let resource_manager =
+                IntakeResourceManager::new(&jobs, &configmaps, &ctx.config, &ctx_arc);
+            resource_manager
+                .create_resources(&intake_run, &job_name)
+                .await?;
+
+            update_intake_status(
+                &intake_run,
+                ctx,
+                "Running",
+                "Project intake started",
+                Some(&job_name),
+            )
+            .await?;
+            Ok(Action::requeue(std::time::Duration::from_secs(30)))
+        }
+        JobState::Running => {
+            // Also records the Job if a restart happened before status learned about it
+            update_intake_status(
+                &intake_run,
+                ctx,
+                "Running",
+                "Project intake in progress",
+                Some(&job_name),
+            )
+            .await?;
+            Ok(Action::requeue(std::time::Duration::from_secs(30)))
+        }
+        JobState::Completed => {
+            info!("Intake job completed successfully");
+            update_intake_status(
+                &intake_run,
+                ctx,
+                "Succeeded",
+                "Project intake completed successfully",
+                Some(&job_name),
+            )
+            .await?;
+            Ok(Action::await_change())
+        }
+        JobState::Failed => {
+            info!("Intake job failed");
+            update_intake_status(
+                &intake_run,
+                ctx,
+                "Failed",
+                "Project intake failed",
+                Some(&job_name),
+            )
+            .await?;
+            Ok(Action::await_change())
+        }
+    }
+}
+
+/// Reject runs the intake script can't act on before creating anything
+fn validate_intake_run(intake_run: &IntakeRun) -> std::result::Result<(), String> {
+    let spec = &intake_run.spec;
+    let problems: Vec<String> = [
+        ("projectName", spec.project_name.as_str()),
+        ("repositoryUrl", spec.repository_url.as_str()),
+        ("githubApp", spec.github_app.as_str()),
+        ("model", spec.model.as_str()),
+        ("prdContent", spec.prd_content.as_str()),
+    ]
+    .iter()
+    .filter(|(_, value)| value.trim().is_empty())
+    .map(|(field, _)| format!("spec.{field} must not be empty"))
+    .collect();
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems.join("; "))
+    }
+}
+
+async fn update_intake_status(
+    intake_run: &IntakeRun,
+    ctx: &Context,
+    phase: &str,
+    message: &str,
+    job_name: Option<&str>,
+) -> Result<()> {
+    let status = intake_run.status.as_ref();
+    if status.is_some_and(|s| s.phase == phase && s.job_name.as_deref() == job_name) {
+        debug!("Status already '{}', skipping update", phase);
+        return Ok(());
+    }
+
+    let intakeruns: Api<IntakeRun> = Api::namespaced(ctx.client.clone(), &ctx.namespace);
+    let status_patch = json!({
+        "status": {
+            "phase": phase,
+            "message": message,
+            "lastUpdate": chrono::Utc::now().to_rfc3339(),
+            "jobName": job_name,
+            "configmapName": job_name.map(|_| generate_configmap_name(intake_run)),
+            "workCompleted": phase == "Succeeded",
+        }
+    });
+    intakeruns
+        .patch_status(
+            &intake_run.name_any(),
+            &PatchParams::default(),
+            &Patch::Merge(&status_patch),
+        )
+        .await
+        .for_resource(
+            "update status of",
+            "IntakeRun",
+            &intake_run.name_any(),
+            &ctx.namespace,
+        )?;
+
+    info!("IntakeRun status updated to '{}'", phase);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intake_run_requires_prd_and_target() {
+        let intake_run: IntakeRun = serde_json::from_value(json!({
+            "apiVersion": "agents.platform/v1",
+            "kind": "IntakeRun",
+            "metadata": { "name": "market-research" },
+            "spec": {
+                "projectName": "market-research",
+                "repositoryUrl": "",
+                "githubApp": "5DLabs-Morgan",
+                "model": "claude-opus-4-20250514",
+                "prdContent": " "
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            validate_intake_run(&intake_run).unwrap_err(),
+            "spec.repositoryUrl must not be empty; spec.prdContent must not be empty"
+        );
+    }
+}
@@ -0,0 +1,4 @@
+pub mod controller;
+pub mod resources;
+
+pub use controller::*;
@@ -0,0 +1,313 @@
+use crate::crds::IntakeRun;
+use crate::tasks::config::ControllerConfig;
+use crate::tasks::template_check;
+use crate::tasks::types::{
+    build_resource, github_app_secret_name, resource_name, Context, Error, KubeResultExt, Result,
+};
+use k8s_openapi::api::{batch::v1::Job, core::v1::ConfigMap};
+use kube::api::{Api, PostParams};
+use kube::ResourceExt;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tracing::info;
+
+/// Intake script shipped in the claude-templates ConfigMap
+const INTAKE_SCRIPT_KEY: &str = "intake_intake.sh";
+
+/// Script intake.sh sources to trust the custom CA bundle, shared with the code and docs runs
+const CA_TRUST_SCRIPT_KEY: &str = "shared_ca-trust.sh";
+
+pub struct IntakeResourceManager<'a> {
+    pub jobs: &'a Api<Job>,
+    pub configmaps: &'a Api<ConfigMap>,
+    pub config: &'a Arc<ControllerConfig>,
+    pub ctx: &'a Arc<Context>,
+}
+
+impl<'a> IntakeResourceManager<'a> {
+    pub fn new(
+        jobs: &'a Api<Job>,
+        configmaps: &'a Api<ConfigMap>,
+        config: &'a Arc<ControllerConfig>,
+        ctx: &'a Arc<Context>,
+    ) -> Self {
+        Self {
+            jobs,
+            configmaps,
+            config,
+            ctx,
+        }
+    }
+
+    /// Create the intake ConfigMap and Job; both are owned by the `IntakeRun`, so Kubernetes
+    /// garbage-collects them with it. Existing resources from an earlier reconcile are kept.
+    pub async fn create_resources(&self, intake_run: &IntakeRun, job_name: &str) -> Result<String> {
+        let cm_name = generate_configmap_name(intake_run);
+        let script = load_script(INTAKE_SCRIPT_KEY)?;
+        let ca_trust_script = load_script(CA_TRUST_SCRIPT_KEY)?;
+
+        let owner = self.config.owner_for(Some(&intake_run.spec.github_app));
+        let configmap = build_configmap(intake_run, &cm_name, &script, &ca_trust_script, owner)?;
+        match self
+            .configmaps
+            .create(&PostParams::default(), &configmap)
+            .await
+        {
+            Ok(_) => info!("Created intake ConfigMap: {}", cm_name),
+            Err(kube::Error::Api(ae)) if ae.code == 409 => {
+                info!("Intake ConfigMap {} already exists", cm_name);
+            }
+            Err(e) => {
+                return Err(e).for_resource("create", "ConfigMap", &cm_name, &self.ctx.namespace)
+            }
+        }
+
+        let job = self.build_job_spec(intake_run, job_name, &cm_name)?;
+        match self.jobs.create(&PostParams::default(), &job).await {
+            Ok(_) => info!("Created intake job: {}", job_name),
+            Err(kube::Error::Api(ae)) if ae.code == 409 => {
+                info!("Intake job {} already exists", job_name);
+            }
+            Err(e) => return Err(e).for_resource("create", "Job", job_name, &self.ctx.namespace),
+        }
+
+        Ok(cm_name)
+    }
+
+    fn build_job_spec(&self, intake_run: &IntakeRun, job_name: &str, cm_name: &str) -> Result<Job> {
+        let labels = task_labels(
+            intake_run,
+            self.config.owner_for(Some(&intake_run.spec.github_app)),
+        );
+        let app_secret = github_app_secret_name(&intake_run.spec.github_app);
+        let app_env = |name: &str, key: &str| {
+            json!({
+                "name": name,
+                "valueFrom": {
+                    "secretKeyRef": { "name": app_secret, "key": key, "optional": true }
+                }
+            })
+        };
+        let mut env_vars = vec![
+            json!({
+                "name": "ANTHROPIC_API_KEY",
+                "valueFrom": {
+                    "secretKeyRef": {
+                        "name": self.config.secrets.api_key_secret_name,
+                        "key": self.config.secrets.api_key_secret_key
+                    }
+                }
+            }),
+            app_env("GITHUB_APP_ID", "app-id"),
+            app_env("GITHUB_APP_PRIVATE_KEY", "private-key"),
+            app_env("GITHUB_APP_CLIENT_ID", "client-id"),
+            app_env("GITHUB_APP_CLIENT_SECRET", "client-secret"),
+        ];
+        let mut volume_mounts = vec![
+            json!({ "name": "intake-files", "mountPath": "/intake-files", "readOnly": true }),
+            json!({ "name": "agents-config", "mountPath": "/config/agents", "readOnly": true }),
+            json!({ "name": "workspace", "mountPath": "/workspace" }),
+        ];
+        let mut volumes = vec![
+            json!({ "name": "intake-files", "configMap": { "name": cm_name } }),
+            json!({ "name": "agents-config", "configMap": { "name": "controller-agents" } }),
+            json!({ "name": "workspace", "emptyDir": {} }),
+        ];
+        if let Some(ca_bundle) = &self.config.agent.ca_bundle {
+            volumes.push(ca_bundle.volume());
+            volume_mounts.push(ca_bundle.volume_mount());
+            env_vars.push(ca_bundle.env_var());
+        }
+
+        let image = format!(
+            "{}:{}",
+            self.config.agent.image.repository, self.config.agent.image.tag
+        );
+
+        build_resource(
+            "Job",
+            json!({
+                "apiVersion": "batch/v1",
+                "kind": "Job",
+                "metadata": {
+                    "name": job_name,
+                    "labels": labels,
+                    "ownerReferences": [owner_reference(intake_run)]
+                },
+                "spec": {
+                    "backoffLimit": 0,
+                    "activeDeadlineSeconds": self.config.job.active_deadline_seconds,
+                    "template": {
+                        "metadata": { "labels": labels },
+                        "spec": {
+                            "restartPolicy": "Never",
+                            "containers": [{
+                                "name": "intake",
+                                "image": image,
+                                "command": ["/bin/bash"],
+                                "args": ["/intake-files/intake.sh"],
+                                "workingDir": "/workspace",
+                                "env": env_vars,
+                                "volumeMounts": volume_mounts,
+                                "resources": self.config.agent.resources.intake
+                            }],
+                            "volumes": volumes
+                        }
+                    }
+                }
+            }),
+        )
+    }
+}
+
+/// Script `key` from the claude-templates ConfigMap
+fn load_script(key: &str) -> Result<String> {
+    let script_path = template_check::templates_dir().join(key);
+    std::fs::read_to_string(&script_path).map_err(|e| {
+        Error::ConfigError(format!(
+            "Failed to load intake script {}: {e}",
+            script_path.display()
+        ))
+    })
+}
+
+pub(super) fn generate_configmap_name(intake_run: &IntakeRun) -> String {
+    resource_name(&format!("{}-files", intake_run_key(intake_run)))
+}
+
+pub(super) fn generate_job_name(intake_run: &IntakeRun) -> String {
+    resource_name(&intake_run_key(intake_run))
+}
+
+fn intake_run_key(intake_run: &IntakeRun) -> String {
+    let namespace = intake_run
+        .metadata
+        .namespace
+        .as_deref()
+        .unwrap_or("default");
+    let name = intake_run.name_any();
+    let uid_suffix = intake_run
+        .metadata
+        .uid
+        .as_deref()
+        .map(|uid| &uid[..8])
+        .unwrap_or("nouid");
+
+    format!("intake-{namespace}-{name}-{uid_suffix}")
+}
+
+fn owner_reference(intake_run: &IntakeRun) -> Value {
+    json!({
+        "apiVersion": "agents.platform/v1",
+        "kind": "IntakeRun",
+        "name": intake_run.name_any(),
+        "uid": intake_run.metadata.uid.clone().unwrap_or_default(),
+        "controller": true,
+        "blockOwnerDeletion": true
+    })
+}
+
+fn task_labels(intake_run: &IntakeRun, owner: &str) -> BTreeMap<String, String> {
+    // resource_name keeps values DNS-safe and within the 63 character label limit
+    let label = |value: &str| resource_name(value).trim_matches('-').to_string();
+    BTreeMap::from([
+        ("app".to_string(), "controller".to_string()),
+        ("component".to_string(), "intake".to_string()),
+        ("job-type".to_string(), "intake".to_string()),
+        (
+            "project-name".to_string(),
+            label(&intake_run.spec.project_name),
+        ),
+        (
+            "github-identity".to_string(),
+            label(&intake_run.spec.github_app),
+        ),
+        ("owner".to_string(), label(owner)),
+    ])
+}
+
+/// ConfigMap the intake script reads: the PRD, optional architecture doc, its settings, the
+/// script itself and the CA trust script it sources
+fn build_configmap(
+    intake_run: &IntakeRun,
+    cm_name: &str,
+    script: &str,
+    ca_trust_script: &str,
+    owner: &str,
+) -> Result<ConfigMap> {
+    let spec = &intake_run.spec;
+    let config_json = json!({
+        "project_name": spec.project_name,
+        "repository_url": spec.repository_url,
+        "source_branch": spec.source_branch,
+        "github_app": spec.github_app,
+        "model": spec.model,
+        "num_tasks": spec.num_tasks,
+        "expand_tasks": spec.expand_tasks,
+        "analyze_complexity": spec.analyze_complexity
+    });
+
+    build_resource(
+        "ConfigMap",
+        json!({
+            "apiVersion": "v1",
+            "kind": "ConfigMap",
+            "metadata": {
+                "name": cm_name,
+                "labels": task_labels(intake_run, owner),
+                "ownerReferences": [owner_reference(intake_run)]
+            },
+            "data": {
+                "prd.txt": spec.prd_content,
+                "architecture.md": spec.architecture_content,
+                "config.json": config_json.to_string(),
+                "intake.sh": script,
+                "ca-trust.sh": ca_trust_script
+            }
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configmap_carries_intake_inputs() {
+        let intake_run: IntakeRun = serde_json::from_value(json!({
+            "apiVersion": "agents.platform/v1",
+            "kind": "IntakeRun",
+            "metadata": { "name": "market-research", "namespace": "agent-platform", "uid": "0123456789" },
+            "spec": {
+                "projectName": "market-research",
+                "repositoryUrl": "https://github.com/5dlabs/projects",
+                "githubApp": "5DLabs-Morgan",
+                "model": "claude-opus-4-20250514",
+                "prdContent": "# Market Research\n"
+            }
+        }))
+        .unwrap();
+
+        let cm_name = generate_configmap_name(&intake_run);
+        assert_eq!(
+            cm_name,
+            "intake-agent-platform-market-research-01234567-files"
+        );
+
+        let configmap =
+            build_configmap(&intake_run, &cm_name, "#!/bin/bash", "#!/bin/sh", "unknown").unwrap();
+        let data = configmap.data.unwrap();
+        assert_eq!(data["prd.txt"], "# Market Research\n");
+        assert_eq!(data["architecture.md"], "");
+        assert_eq!(data["ca-trust.sh"], "#!/bin/sh");
+        let config: Value = serde_json::from_str(&data["config.json"]).unwrap();
+        assert_eq!(config["num_tasks"], 50);
+        assert_eq!(config["source_branch"], "main");
+        assert_eq!(config["expand_tasks"], true);
+        assert_eq!(
+            configmap.metadata.owner_references.unwrap()[0].kind,
+            "IntakeRun"
+        );
+    }
+}
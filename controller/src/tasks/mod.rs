@@ -1,21 +1,29 @@
-use crate::crds::{CodeRun, DocsRun};
+use crate::crds::{CodeRun, DocsRun, IntakeRun};
 use futures::StreamExt;
 use k8s_openapi::api::batch::v1::Job;
-use kube::runtime::controller::{Action, Controller};
+use kube::runtime::controller::{self, Action, Controller};
 use kube::runtime::watcher::Config;
 use kube::{Api, Client, ResourceExt};
 use std::sync::Arc;
+use tokio::task::JoinSet;
 use tracing::{debug, error, info, instrument, Instrument};
 
+pub mod backoff;
 pub mod code;
 pub mod config;
+pub mod configmap;
 pub mod docs;
+pub mod intake;
+pub mod template_check;
+#[cfg(test)]
+pub(crate) mod testing;
 pub mod types;
 
 // Re-export commonly used items
 pub use code::reconcile_code_run;
 pub use config::ControllerConfig;
 pub use docs::reconcile_docs_run;
+pub use intake::reconcile_intake_run;
 pub use types::{Error, Result};
 
 // Context is crate-internal only
@@ -71,40 +79,70 @@ pub async fn run_task_controller(client: Client, namespace: String) -> Result<()
 
     debug!("Controller context created successfully");
 
-    // Run both controllers concurrently
-    info!("Starting DocsRun and CodeRun controllers...");
+    // Run the controllers concurrently
+    info!("Starting DocsRun, CodeRun and IntakeRun controllers...");
 
-    let docs_controller_handle = tokio::spawn({
+    // Owning the controllers in a JoinSet aborts them all when this future is dropped,
+    // e.g. when leadership is lost, so a stale leader stops writing Jobs and status
+    let mut controllers = JoinSet::new();
+    controllers.spawn({
         let context = context.clone();
         let client = client.clone();
         let namespace = namespace.clone();
-        async move { run_docs_controller(client, namespace, context).await }
+        async move {
+            (
+                "DocsRun",
+                run_docs_controller(client, namespace, context).await,
+            )
+        }
     });
-
-    let code_controller_handle = tokio::spawn({
+    controllers.spawn({
         let context = context.clone();
         let client = client.clone();
         let namespace = namespace.clone();
-        async move { run_code_controller(client, namespace, context).await }
+        async move {
+            (
+                "CodeRun",
+                run_code_controller(client, namespace, context).await,
+            )
+        }
+    });
+    controllers.spawn({
+        let context = context.clone();
+        let client = client.clone();
+        let namespace = namespace.clone();
+        async move {
+            (
+                "IntakeRun",
+                run_intake_controller(client, namespace, context).await,
+            )
+        }
     });
 
-    debug!("Both controllers started, waiting for completion...");
-
-    // Wait for both controllers to complete (they should run indefinitely)
-    match tokio::try_join!(docs_controller_handle, code_controller_handle) {
-        Ok((docs_result, code_result)) => {
-            if let Err(e) = docs_result {
-                error!("DocsRun controller failed: {:?}", e);
-            }
-            if let Err(e) = code_result {
-                error!("CodeRun controller failed: {:?}", e);
+    // Trims finished CodeRuns to the configured per-service retention. Owned like the
+    // controllers so it stops with them, but it may finish early when disabled.
+    let mut background = JoinSet::new();
+    background.spawn({
+        let context = context.clone();
+        async move {
+            if let Err(e) = code::retention::run_retention_reaper(context).await {
+                error!("CodeRun retention reaper failed: {:?}", e);
             }
         }
-        Err(e) => {
-            error!("Controller task join error: {:?}", e);
-        }
+    });
+
+    debug!("All controllers started, waiting for completion...");
+
+    // The controllers should run indefinitely; once one stops, returning drops the rest so
+    // the caller can restart cleanly instead of running with a partial set
+    match controllers.join_next().await {
+        Some(Ok((kind, Ok(())))) => error!("{} controller stopped unexpectedly", kind),
+        Some(Ok((kind, Err(e)))) => error!("{} controller failed: {:?}", kind, e),
+        Some(Err(e)) => error!("Controller task join error: {:?}", e),
+        None => {}
     }
 
+    background.abort_all();
     info!("Task controller shutting down");
     Ok(())
 }
@@ -122,9 +160,20 @@ async fn run_docs_controller(
     let jobs_api: Api<Job> = Api::namespaced(client.clone(), &namespace);
     let watcher_config = Config::default().any_semantic();
 
+    let concurrency = context.config.concurrency.docs;
+    info!(
+        "DocsRun reconcile concurrency: {}",
+        if concurrency == 0 {
+            "unbounded".to_string()
+        } else {
+            concurrency.to_string()
+        }
+    );
+
     Controller::new(docs_api, watcher_config.clone())
         .owns(jobs_api, watcher_config)
-        .run(reconcile_docs_run, error_policy_docs, context)
+        .with_config(controller::Config::default().concurrency(concurrency))
+        .run(reconcile_docs, error_policy_docs, context)
         .for_each(|reconciliation_result| {
             let docs_span = tracing::info_span!("docs_reconciliation_result");
             async move {
@@ -164,9 +213,20 @@ async fn run_code_controller(
     let jobs_api: Api<Job> = Api::namespaced(client.clone(), &namespace);
     let watcher_config = Config::default().any_semantic();
 
+    let concurrency = context.config.concurrency.code;
+    info!(
+        "CodeRun reconcile concurrency: {}",
+        if concurrency == 0 {
+            "unbounded".to_string()
+        } else {
+            concurrency.to_string()
+        }
+    );
+
     Controller::new(code_api, watcher_config.clone())
         .owns(jobs_api, watcher_config)
-        .run(reconcile_code_run, error_policy_code, context)
+        .with_config(controller::Config::default().concurrency(concurrency))
+        .run(reconcile_code, error_policy_code, context)
         .for_each(|reconciliation_result| {
             let code_span = tracing::info_span!("code_reconciliation_result");
             async move {
@@ -193,26 +253,77 @@ async fn run_code_controller(
     Ok(())
 }
 
-/// Error policy for DocsRun controller - limit to single retry
-#[instrument(skip(_ctx), fields(docs_run_name = %_docs_run.name_any(), namespace = %_ctx.namespace))]
-fn error_policy_docs(_docs_run: Arc<DocsRun>, error: &Error, _ctx: Arc<Context>) -> Action {
-    error!(
-        error = ?error,
-        docs_run_name = %_docs_run.name_any(),
-        "DocsRun reconciliation failed - no retries, stopping"
-    );
-    // Don't retry - just stop on first failure
-    Action::await_change()
+/// Run the IntakeRun controller
+#[instrument(skip(client, context), fields(namespace = %namespace))]
+async fn run_intake_controller(
+    client: Client,
+    namespace: String,
+    context: Arc<Context>,
+) -> Result<()> {
+    info!("Starting IntakeRun controller");
+
+    let intake_api: Api<IntakeRun> = Api::namespaced(client.clone(), &namespace);
+    let jobs_api: Api<Job> = Api::namespaced(client.clone(), &namespace);
+    let watcher_config = Config::default().any_semantic();
+
+    Controller::new(intake_api, watcher_config.clone())
+        .owns(jobs_api, watcher_config)
+        .run(reconcile_intake_run, error_policy_intake, context)
+        .for_each(|reconciliation_result| {
+            let intake_span = tracing::info_span!("intake_reconciliation_result");
+            async move {
+                match reconciliation_result {
+                    Ok(intake_run_resource) => {
+                        info!(
+                            resource = ?intake_run_resource,
+                            "IntakeRun reconciliation successful"
+                        );
+                    }
+                    Err(reconciliation_err) => {
+                        error!(
+                            error = ?reconciliation_err,
+                            "IntakeRun reconciliation error"
+                        );
+                    }
+                }
+            }
+            .instrument(intake_span)
+        })
+        .await;
+
+    info!("IntakeRun controller shutting down");
+    Ok(())
+}
+
+/// Reconcile a DocsRun, holding off while a backed-off retry is pending
+async fn reconcile_docs(docs_run: Arc<DocsRun>, ctx: Arc<Context>) -> Result<Action> {
+    backoff::reconcile_with_backoff(docs_run, ctx, reconcile_docs_run).await
+}
+
+/// Reconcile a CodeRun, holding off while a backed-off retry is pending
+async fn reconcile_code(code_run: Arc<CodeRun>, ctx: Arc<Context>) -> Result<Action> {
+    backoff::reconcile_with_backoff(code_run, ctx, reconcile_code_run).await
+}
+
+/// Error policy for DocsRun controller - back off on transient API errors
+#[instrument(skip(docs_run, ctx), fields(docs_run_name = %docs_run.name_any(), namespace = %ctx.namespace))]
+fn error_policy_docs(docs_run: Arc<DocsRun>, error: &Error, ctx: Arc<Context>) -> Action {
+    backoff::error_policy(docs_run, error, ctx)
+}
+
+/// Error policy for CodeRun controller - back off on transient API errors
+#[instrument(skip(code_run, ctx), fields(code_run_name = %code_run.name_any(), namespace = %ctx.namespace))]
+fn error_policy_code(code_run: Arc<CodeRun>, error: &Error, ctx: Arc<Context>) -> Action {
+    backoff::error_policy(code_run, error, ctx)
 }
 
-/// Error policy for CodeRun controller - limit to single retry
-#[instrument(skip(_ctx), fields(code_run_name = %_code_run.name_any(), namespace = %_ctx.namespace))]
-fn error_policy_code(_code_run: Arc<CodeRun>, error: &Error, _ctx: Arc<Context>) -> Action {
+/// Error policy for IntakeRun controller - no retries
+#[instrument(skip(_ctx), fields(intake_run_name = %_intake_run.name_any(), namespace = %_ctx.namespace))]
+fn error_policy_intake(_intake_run: Arc<IntakeRun>, error: &Error, _ctx: Arc<Context>) -> Action {
     error!(
         error = ?error,
-        code_run_name = %_code_run.name_any(),
-        "CodeRun reconciliation failed - no retries, stopping"
+        intake_run_name = %_intake_run.name_any(),
+        "IntakeRun reconciliation failed - no retries, stopping"
     );
-    // Don't retry - just stop on first failure
     Action::await_change()
 }
@@ -0,0 +1,343 @@
+//! Offline validation of the Claude templates
+//!
+//! `agent-controller --check-templates [DIR]` renders every code and docs template through
+//! the real generators in Handlebars strict mode, so a template that references a variable
+//! the controller never provides fails in CI instead of rendering as an empty string.
+//!
+//! Operators can layer prompt fragments over the built-in templates without forking them:
+//! each file in the fragments directory (mounted from the `prompt-fragments` ConfigMap) is
+//! registered as a Handlebars partial named after its key up to the first `.`, e.g.
+//! `docs_guidance.md.hbs` fills the `docs_guidance` insertion point. Fragments render with the
+//! same variables as the template that includes them.
+
+use crate::crds::{CodeRun, DocsRun};
+use crate::tasks::code::templates::CodeTemplateGenerator;
+use crate::tasks::config::ControllerConfig;
+use crate::tasks::docs::templates::DocsTemplateGenerator;
+use handlebars::Handlebars;
+use serde_json::json;
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Template base path (mounted from ConfigMap)
+const CLAUDE_TEMPLATES_PATH: &str = "/claude-templates";
+
+/// Prompt fragment path (mounted from the optional `prompt-fragments` ConfigMap)
+const PROMPT_FRAGMENTS_PATH: &str = "/prompt-fragments";
+
+/// Partials the built-in templates include; each renders nothing unless a fragment fills it.
+/// Names must not collide with the generators' template names, or a template includes itself.
+pub const INSERTION_POINTS: &[&str] = &[
+    "code_memory",
+    "code_guidelines",
+    "docs_guidance",
+    "docs_memory",
+];
+
+/// Template and fragment directories used while a check runs
+struct CheckDirs {
+    templates: PathBuf,
+    fragments: Option<PathBuf>,
+}
+
+thread_local! {
+    /// Set while a check runs: templates come from here and render strictly
+    static CHECK_DIR: RefCell<Option<CheckDirs>> = const { RefCell::new(None) };
+}
+
+/// Directory the generators load templates from
+pub(crate) fn templates_dir() -> PathBuf {
+    CHECK_DIR
+        .with(|dir| dir.borrow().as_ref().map(|dirs| dirs.templates.clone()))
+        .unwrap_or_else(|| PathBuf::from(CLAUDE_TEMPLATES_PATH))
+}
+
+/// Directory prompt fragments are loaded from, if any
+fn fragments_dir() -> Option<PathBuf> {
+    CHECK_DIR.with(|dir| match dir.borrow().as_ref() {
+        Some(dirs) => dirs.fragments.clone(),
+        None => Some(PathBuf::from(PROMPT_FRAGMENTS_PATH)),
+    })
+}
+
+/// Whether template problems should be errors rather than logged and skipped
+pub(crate) fn is_checking() -> bool {
+    CHECK_DIR.with(|dir| dir.borrow().is_some())
+}
+
+/// Handlebars registry for the generators, with prompt fragments registered as partials;
+/// strict only while checking
+pub(crate) fn handlebars() -> Handlebars<'static> {
+    let mut handlebars = Handlebars::new();
+    handlebars.set_strict_mode(is_checking());
+    for name in INSERTION_POINTS {
+        handlebars
+            .register_partial(name, "")
+            .expect("empty partial parses");
+    }
+    for (name, content) in fragments_dir()
+        .map(|dir| read_fragments(&dir))
+        .unwrap_or_default()
+    {
+        if let Err(e) = handlebars.register_partial(&name, content) {
+            warn!("Skipping prompt fragment {name}: {e}");
+        }
+    }
+    handlebars
+}
+
+/// `(partial name, content)` for each fragment file in `dir`; a missing directory has none
+fn read_fragments(dir: &Path) -> Vec<(String, String)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut fragments: Vec<(String, String)> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter_map(|path| {
+            let file_name = path.file_name()?.to_str()?;
+            // Skip the `..data` links and timestamped directories of a mounted ConfigMap
+            if file_name.starts_with('.') || !path.is_file() {
+                return None;
+            }
+            let name = file_name.split('.').next()?.to_string();
+            let content = std::fs::read_to_string(&path).ok()?;
+            Some((name, content))
+        })
+        .collect();
+    fragments.sort();
+    fragments
+}
+
+/// Problems with the fragments in `dir`: unknown insertion points and templates that don't parse
+fn check_fragments(dir: &Path) -> Vec<String> {
+    let mut problems = Vec::new();
+    for (name, content) in read_fragments(dir) {
+        if !INSERTION_POINTS.contains(&name.as_str()) {
+            problems.push(format!(
+                "Prompt fragment {name}: not an insertion point (expected one of: {})",
+                INSERTION_POINTS.join(", ")
+            ));
+        } else if let Err(e) = Handlebars::new().register_partial(&name, content) {
+            problems.push(format!("Prompt fragment {name}: {e}"));
+        }
+    }
+    problems
+}
+
+/// Render all templates under `template_dir` and return one message per failure
+///
+/// `template_dir` may be the mounted ConfigMap (flat `code_container.sh.hbs` keys) or the
+/// chart's `claude-templates` source tree, which is flattened the same way Helm does. When
+/// `fragments_dir` is given, its prompt fragments are validated and rendered into the samples.
+pub fn check_templates(
+    template_dir: &Path,
+    fragments_dir: Option<&Path>,
+    config: &ControllerConfig,
+) -> Vec<String> {
+    let staging =
+        std::env::temp_dir().join(format!("claude-templates-check-{}", std::process::id()));
+    if let Err(e) = stage_templates(template_dir, &staging) {
+        return vec![format!(
+            "Failed to read templates from {}: {e}",
+            template_dir.display()
+        )];
+    }
+
+    let mut problems = fragments_dir.map(check_fragments).unwrap_or_default();
+    CHECK_DIR.with(|dir| {
+        *dir.borrow_mut() = Some(CheckDirs {
+            templates: staging.clone(),
+            fragments: fragments_dir.map(Path::to_path_buf),
+        })
+    });
+    problems.extend(render_samples(config));
+    CHECK_DIR.with(|dir| *dir.borrow_mut() = None);
+
+    let _ = std::fs::remove_dir_all(&staging);
+    problems
+}
+
+/// Load templates from a staged copy of the chart's `claude-templates` on this thread, as the
+/// mounted ConfigMap would provide them; the copy is removed when the guard drops
+#[cfg(test)]
+pub(crate) fn use_chart_templates() -> tempfile::TempDir {
+    let chart_templates =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../infra/charts/controller/claude-templates");
+    let staging = tempfile::tempdir().expect("temporary template directory");
+    stage_templates(&chart_templates, staging.path()).expect("chart templates are readable");
+    CHECK_DIR.with(|dir| {
+        *dir.borrow_mut() = Some(CheckDirs {
+            templates: staging.path().to_path_buf(),
+            fragments: None,
+        })
+    });
+    staging
+}
+
+/// Copy templates into `staging` under their ConfigMap keys (`code/hooks/x.hbs` -> `code_hooks_x.hbs`)
+fn stage_templates(source: &Path, staging: &Path) -> std::io::Result<()> {
+    fn visit(root: &Path, dir: &Path, staging: &Path) -> std::io::Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                visit(root, &path, staging)?;
+            } else if let Ok(relative) = path.strip_prefix(root) {
+                let key = relative.to_string_lossy().replace(['/', '\\'], "_");
+                std::fs::copy(&path, staging.join(key))?;
+            }
+        }
+        Ok(())
+    }
+
+    let _ = std::fs::remove_dir_all(staging);
+    std::fs::create_dir_all(staging)?;
+    visit(source, source, staging)
+}
+
+/// Render representative CodeRuns and DocsRuns, covering the optional template branches
+fn render_samples(config: &ControllerConfig) -> Vec<String> {
+    let mut full_config = config.clone();
+    full_config.telemetry.enabled = !config.telemetry.enabled;
+    full_config.permissions.agent_tools_override = true;
+
+    let code_base = json!({
+        "taskId": 42,
+        "service": "simple-api",
+        "repositoryUrl": "https://github.com/5dlabs/example",
+        "docsRepositoryUrl": "https://github.com/5dlabs/docs",
+        "docsProjectDirectory": "_projects/simple-api",
+        "workingDirectory": "services/simple-api",
+        "model": "claude-sonnet-4-20250514",
+        "githubApp": "5DLabs-Rex"
+    });
+    let mut code_full = code_base.clone();
+    code_full["continueSession"] = json!(true);
+    code_full["baseBranch"] = json!("release/1.2");
+    code_full["initCommands"] = json!(["npm ci"]);
+    code_full["repositoryVisibility"] = json!("public");
+    code_full["workingBranch"] = json!("agent/{user}/task-{task_id}-{timestamp}");
+    code_full["commitSha"] = json!("3f2a9c1d4e5b6a7980716253443526170819aabb");
+    code_full["gitConfig"] = json!({ "http.postBuffer": "524288000" });
+
+    let docs_base = json!({
+        "repositoryUrl": "https://github.com/5dlabs/example",
+        "workingDirectory": "_projects/simple-api",
+        "sourceBranch": "main",
+        "model": "claude-opus-4-20250514",
+        "githubApp": "5DLabs-Morgan"
+    });
+    let mut docs_full = docs_base.clone();
+    docs_full["includeCodebase"] = json!(true);
+    docs_full["changedOnly"] = json!(true);
+    docs_full["outputFormat"] = json!("rst");
+    docs_full["architectureFile"] = json!("docs/architecture.md");
+    docs_full["prdFile"] = json!("docs/prd.md");
+    docs_full["taskmasterDir"] = json!("services/api/.taskmaster");
+    docs_full["promptModification"] = json!("The last run missed task 7");
+
+    let mut problems = Vec::new();
+    for (label, spec, config) in [
+        ("CodeRun (defaults)", &code_base, config),
+        ("CodeRun (all options)", &code_full, &full_config),
+    ] {
+        let code_run: CodeRun = sample("CodeRun", spec, json!({ "phase": "Running" }));
+        if let Err(e) = CodeTemplateGenerator::generate_all_templates(&code_run, config) {
+            problems.push(format!("{label}: {e}"));
+        }
+    }
+    for (label, spec, config) in [
+        ("DocsRun (defaults)", &docs_base, config),
+        ("DocsRun (all options)", &docs_full, &full_config),
+    ] {
+        let status = json!({
+            "phase": "Running",
+            "changedSince": "0123456789abcdef0123456789abcdef01234567"
+        });
+        let docs_run: DocsRun = sample("DocsRun", spec, status);
+        if let Err(e) = DocsTemplateGenerator::generate_all_templates(&docs_run, config) {
+            problems.push(format!("{label}: {e}"));
+        }
+    }
+    problems
+}
+
+fn sample<T: serde::de::DeserializeOwned>(
+    kind: &str,
+    spec: &serde_json::Value,
+    status: serde_json::Value,
+) -> T {
+    serde_json::from_value(json!({
+        "apiVersion": "agents.platform/v1",
+        "kind": kind,
+        "metadata": { "name": "template-check", "namespace": "agent-platform" },
+        "spec": spec,
+        "status": status
+    }))
+    .expect("sample resources match the CRD schema")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chart_templates_render_strictly() {
+        let chart_templates = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../infra/charts/controller/claude-templates");
+        let problems = check_templates(&chart_templates, None, &ControllerConfig::default());
+        assert!(problems.is_empty(), "{problems:#?}");
+    }
+
+    #[test]
+    fn test_prompt_fragments_fill_insertion_points() {
+        let chart_templates = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../infra/charts/controller/claude-templates");
+        let fragments =
+            std::env::temp_dir().join(format!("prompt-fragments-test-{}", std::process::id()));
+        std::fs::create_dir_all(fragments.join("..2025_01_01")).unwrap();
+        std::fs::write(
+            fragments.join("code_memory.md.hbs"),
+            "## House Style\n\nPrefix commits for {{service}} with the task ID.\n",
+        )
+        .unwrap();
+
+        let config = ControllerConfig::default();
+        assert_eq!(
+            check_templates(&chart_templates, Some(&fragments), &config),
+            Vec::<String>::new()
+        );
+
+        let staging = fragments.join("staging");
+        stage_templates(&chart_templates, &staging).unwrap();
+        CHECK_DIR.with(|dir| {
+            *dir.borrow_mut() = Some(CheckDirs {
+                templates: staging.clone(),
+                fragments: Some(fragments.clone()),
+            })
+        });
+        let code_run: CodeRun = sample(
+            "CodeRun",
+            &json!({
+                "taskId": 7,
+                "service": "simple-api",
+                "repositoryUrl": "https://github.com/5dlabs/example",
+                "docsRepositoryUrl": "https://github.com/5dlabs/docs",
+                "model": "claude-sonnet-4-20250514"
+            }),
+            json!({ "phase": "Running" }),
+        );
+        let rendered = CodeTemplateGenerator::generate_all_templates(&code_run, &config);
+        CHECK_DIR.with(|dir| *dir.borrow_mut() = None);
+        assert!(rendered.unwrap()["CLAUDE.md"]
+            .contains("Prefix commits for simple-api with the task ID."));
+
+        std::fs::write(fragments.join("docs_guidance.md.hbs"), "{{#if service}}").unwrap();
+        std::fs::write(fragments.join("house_style.md.hbs"), "Be terse.").unwrap();
+        let problems = check_templates(&chart_templates, Some(&fragments), &config);
+        let _ = std::fs::remove_dir_all(&fragments);
+        assert_eq!(problems.len(), 2, "{problems:#?}");
+        assert!(problems[0].starts_with("Prompt fragment docs_guidance:"));
+        assert!(problems[1].contains("house_style: not an insertion point"));
+    }
+}
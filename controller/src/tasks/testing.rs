@@ -0,0 +1,185 @@
+//! In-memory Kubernetes API server for reconcile tests
+//!
+//! `FakeApiServer` serves the subset of the API the reconcilers use (get, list, create,
+//! replace, merge patch and delete on namespaced objects) from a map keyed by object path,
+//! so a test can seed a CodeRun, run `reconcile_*` against a real `kube::Client` and then
+//! assert on the objects the controller created.
+
+use crate::tasks::config::ControllerConfig;
+use crate::tasks::types::Context;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+pub(crate) struct FakeApiServer {
+    server: MockServer,
+    objects: Arc<Mutex<BTreeMap<String, Value>>>,
+}
+
+impl FakeApiServer {
+    pub(crate) async fn start() -> Self {
+        let server = MockServer::start().await;
+        let objects = Arc::new(Mutex::new(BTreeMap::new()));
+        let store = objects.clone();
+        Mock::given(wiremock::matchers::any())
+            .respond_with(move |request: &Request| respond(&store, request))
+            .mount(&server)
+            .await;
+        Self { server, objects }
+    }
+
+    /// Client that talks to this server
+    pub(crate) fn client(&self, namespace: &str) -> kube::Client {
+        let mut kube_config = kube::Config::new(self.server.uri().parse().unwrap());
+        kube_config.default_namespace = namespace.to_string();
+        kube::Client::try_from(kube_config).unwrap()
+    }
+
+    /// Controller context whose client talks to this server
+    pub(crate) fn context(&self, namespace: &str, config: ControllerConfig) -> Arc<Context> {
+        Arc::new(Context {
+            client: self.client(namespace),
+            namespace: namespace.to_string(),
+            config: Arc::new(config),
+        })
+    }
+
+    /// Store `object` at its API path, e.g. `/apis/agents.platform/v1/namespaces/ns/coderuns/x`
+    pub(crate) fn insert(&self, path: &str, object: Value) {
+        self.objects
+            .lock()
+            .unwrap()
+            .insert(path.to_string(), object);
+    }
+
+    /// Drop the object at `path`, e.g. to stand in for garbage collection or a Job's TTL
+    pub(crate) fn remove(&self, path: &str) -> Option<Value> {
+        self.objects.lock().unwrap().remove(path)
+    }
+
+    pub(crate) fn get(&self, path: &str) -> Option<Value> {
+        self.objects.lock().unwrap().get(path).cloned()
+    }
+
+    /// Names of the objects stored directly under a collection path
+    pub(crate) fn names_in(&self, collection: &str) -> Vec<String> {
+        let prefix = format!("{collection}/");
+        self.objects
+            .lock()
+            .unwrap()
+            .keys()
+            .filter_map(|path| path.strip_prefix(&prefix))
+            .map(String::from)
+            .collect()
+    }
+}
+
+fn respond(store: &Mutex<BTreeMap<String, Value>>, request: &Request) -> ResponseTemplate {
+    let path = request.url.path().trim_end_matches('/');
+    // Status patches and replaces apply to the parent object
+    let object_path = path.strip_suffix("/status").unwrap_or(path);
+    let mut objects = store.lock().unwrap();
+
+    match request.method.as_str() {
+        "GET" => match objects.get(object_path) {
+            Some(object) => ResponseTemplate::new(200).set_body_json(object),
+            None if is_collection(path) => {
+                let prefix = format!("{path}/");
+                let items: Vec<&Value> = objects
+                    .iter()
+                    .filter(|(key, _)| {
+                        key.strip_prefix(&prefix)
+                            .is_some_and(|name| !name.contains('/'))
+                    })
+                    .map(|(_, object)| object)
+                    .collect();
+                ResponseTemplate::new(200).set_body_json(json!({
+                    "apiVersion": "v1",
+                    "kind": "List",
+                    "metadata": { "resourceVersion": "1" },
+                    "items": items
+                }))
+            }
+            None => status(404, "NotFound", path),
+        },
+        "POST" => {
+            let Ok(mut object) = request.body_json::<Value>() else {
+                return status(400, "BadRequest", path);
+            };
+            let name = object["metadata"]["name"].as_str().unwrap_or_default();
+            let created_path = format!("{path}/{name}");
+            if objects.contains_key(&created_path) {
+                return status(409, "AlreadyExists", &created_path);
+            }
+            object["metadata"]["uid"] = json!(format!("uid-{}", objects.len() + 1));
+            object["metadata"]["resourceVersion"] = json!("1");
+            objects.insert(created_path, object.clone());
+            ResponseTemplate::new(201).set_body_json(object)
+        }
+        "PUT" => {
+            let Ok(object) = request.body_json::<Value>() else {
+                return status(400, "BadRequest", path);
+            };
+            objects.insert(object_path.to_string(), object.clone());
+            ResponseTemplate::new(200).set_body_json(object)
+        }
+        "PATCH" => {
+            let (Some(object), Ok(patch)) =
+                (objects.get_mut(object_path), request.body_json::<Value>())
+            else {
+                return status(404, "NotFound", path);
+            };
+            merge_patch(object, &patch);
+            ResponseTemplate::new(200).set_body_json(object.clone())
+        }
+        "DELETE" => match objects.remove(object_path) {
+            Some(object) => ResponseTemplate::new(200).set_body_json(object),
+            None => status(404, "NotFound", path),
+        },
+        _ => status(405, "MethodNotAllowed", path),
+    }
+}
+
+/// `/api/v1/namespaces/ns/configmaps` is a collection; `.../configmaps/name` is an object
+fn is_collection(path: &str) -> bool {
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let after_namespace = segments
+        .iter()
+        .position(|s| *s == "namespaces")
+        .map(|i| segments.len().saturating_sub(i + 2));
+    match after_namespace {
+        Some(remaining) => remaining == 1,
+        None => true,
+    }
+}
+
+fn status(code: u16, reason: &str, path: &str) -> ResponseTemplate {
+    ResponseTemplate::new(code).set_body_json(json!({
+        "apiVersion": "v1",
+        "kind": "Status",
+        "status": "Failure",
+        "message": format!("{path}: {reason}"),
+        "reason": reason,
+        "code": code
+    }))
+}
+
+/// JSON merge patch (RFC 7386)
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = json!({});
+    }
+    let target = target.as_object_mut().unwrap();
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use super::config::ControllerConfig;
-use kube::Client;
+use k8s_openapi::api::batch::v1::Job;
+use kube::{Api, Client};
+use serde::de::DeserializeOwned;
 use std::sync::Arc;
 
 // Error type for the controller
@@ -17,10 +19,85 @@ pub enum Error {
 
     #[error("Task configuration error: {0}")]
     ConfigError(String),
+
+    #[error("Failed to build {kind}: invalid value at `{path}`: {source}")]
+    ResourceBuildError {
+        kind: &'static str,
+        path: String,
+        source: serde_json::Error,
+    },
+
+    #[error("failed to {action} {kind} '{name}' in ns '{namespace}': {source}")]
+    ResourceError {
+        action: &'static str,
+        kind: &'static str,
+        name: String,
+        namespace: String,
+        source: Box<kube::Error>,
+    },
+}
+
+impl Error {
+    /// HTTP status of the Kubernetes API error behind this error, if there is one
+    pub fn api_status(&self) -> Option<u16> {
+        let source = match self {
+            Error::KubeError(source) => source,
+            Error::ResourceError { source, .. } => source.as_ref(),
+            _ => return None,
+        };
+        match source {
+            kube::Error::Api(ae) => Some(ae.code),
+            _ => None,
+        }
+    }
+
+    /// Whether retrying may succeed: API conflicts, throttling and server errors, or a
+    /// dropped connection; invalid specs and configuration never are
+    pub fn is_transient(&self) -> bool {
+        let source = match self {
+            Error::KubeError(source) => source,
+            Error::ResourceError { source, .. } => source.as_ref(),
+            _ => return false,
+        };
+        match source {
+            kube::Error::Api(ae) => matches!(ae.code, 409 | 429 | 500 | 503),
+            kube::Error::HyperError(_) | kube::Error::Service(_) => true,
+            _ => false,
+        }
+    }
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
+/// Attach the resource an API call concerned to its `kube::Error`
+pub trait KubeResultExt<T> {
+    fn for_resource(
+        self,
+        action: &'static str,
+        kind: &'static str,
+        name: &str,
+        namespace: &str,
+    ) -> Result<T>;
+}
+
+impl<T> KubeResultExt<T> for std::result::Result<T, kube::Error> {
+    fn for_resource(
+        self,
+        action: &'static str,
+        kind: &'static str,
+        name: &str,
+        namespace: &str,
+    ) -> Result<T> {
+        self.map_err(|source| Error::ResourceError {
+            action,
+            kind,
+            name: name.to_string(),
+            namespace: namespace.to_string(),
+            source: Box::new(source),
+        })
+    }
+}
+
 // Context shared across controller operations
 #[derive(Clone)]
 pub struct Context {
@@ -29,9 +106,93 @@ pub struct Context {
     pub config: Arc<ControllerConfig>,
 }
 
+/// Deserialize a `json!`-built manifest, reporting the JSON path of any invalid field
+pub fn build_resource<T: DeserializeOwned>(
+    kind: &'static str,
+    value: serde_json::Value,
+) -> Result<T> {
+    serde_path_to_error::deserialize(value).map_err(|err| Error::ResourceBuildError {
+        kind,
+        path: err.path().to_string(),
+        source: err.into_inner(),
+    })
+}
+
 // Finalizer names for cleanup
 pub(crate) const DOCS_FINALIZER_NAME: &str = "docsruns.orchestrator.io/finalizer";
 pub(crate) const CODE_FINALIZER_NAME: &str = "coderuns.orchestrator.io/finalizer";
+pub(crate) const INTAKE_FINALIZER_NAME: &str = "intakeruns.orchestrator.io/finalizer";
+
+// Annotation marking a workspace PVC as held for post-failure inspection
+pub(crate) const PRESERVED_WORKSPACE_ANNOTATION: &str = "agents.platform/preserved-for";
+
+/// Longest generated name; Job names also become the pods' `job-name` label value
+pub(crate) const MAX_RESOURCE_NAME_LEN: usize = 63;
+
+/// Turn `raw` into a DNS-1123 label usable as a Job or ConfigMap name
+///
+/// Names over 63 characters are truncated and suffixed with a hash of the full name, so
+/// verbose agent or service names stay unique and map to the same name on every reconcile.
+pub fn resource_name(raw: &str) -> String {
+    let name: String = raw
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    if name.len() <= MAX_RESOURCE_NAME_LEN {
+        return name;
+    }
+
+    let hash = fnv1a(name.bytes());
+    let suffix = format!("-{:010x}", hash & 0xff_ffff_ffff);
+    let prefix = name[..MAX_RESOURCE_NAME_LEN - suffix.len()].trim_end_matches('-');
+    format!("{prefix}{suffix}")
+}
+
+/// The name `raw` got before `resource_name` capped lengths: sanitized but never truncated
+pub(crate) fn legacy_resource_name(raw: &str) -> String {
+    raw.replace(['_', '.'], "-").to_lowercase()
+}
+
+/// Name of the Job for `raw`: `resource_name(raw)`, unless a Job from an older controller
+/// still exists under the legacy name, so a run in flight across the upgrade keeps its Job
+pub(crate) async fn resolve_job_name(
+    jobs: &Api<Job>,
+    raw: &str,
+    namespace: &str,
+) -> Result<String> {
+    let name = resource_name(raw);
+    let legacy = legacy_resource_name(raw);
+    if legacy != name
+        && jobs
+            .get_opt(&legacy)
+            .await
+            .for_resource("get", "Job", &legacy, namespace)?
+            .is_some()
+    {
+        return Ok(legacy);
+    }
+    Ok(name)
+}
+
+/// Whether a run's status still needs to adopt `job_name`: the Job exists but the controller
+/// stopped (e.g. crashed) before recording it, so the status never learned about it
+pub fn job_needs_adoption(recorded_job: Option<&str>, job_name: &str) -> bool {
+    recorded_job != Some(job_name)
+}
+
+/// 64-bit FNV-1a hash, for names and fingerprints that must not change between controller
+/// builds (unlike std's `DefaultHasher`)
+pub(crate) fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    bytes.into_iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
 
 // Helper functions for SSH and GitHub token secret names
 pub fn ssh_secret_name(github_user: &str) -> String {
@@ -48,3 +209,93 @@ pub fn github_app_secret_name(github_app: &str) -> String {
     let normalized = github_app.to_lowercase().replace(['_', ' '], "-");
     format!("github-app-{normalized}")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k8s_openapi::api::batch::v1::Job;
+    use serde_json::json;
+
+    #[test]
+    fn test_build_resource_reports_field_path() {
+        let job = json!({
+            "apiVersion": "batch/v1",
+            "kind": "Job",
+            "metadata": { "name": "example" },
+            "spec": {
+                "backoffLimit": "three",
+                "template": { "spec": { "containers": [] } }
+            }
+        });
+
+        let err = build_resource::<Job>("Job", job).unwrap_err();
+        assert!(
+            err.to_string().contains("`spec.backoffLimit`"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn test_resource_error_names_the_resource() {
+        let forbidden = kube::Error::Api(kube::error::ErrorResponse {
+            status: "Failure".to_string(),
+            message: "jobs.batch is forbidden".to_string(),
+            reason: "Forbidden".to_string(),
+            code: 403,
+        });
+
+        let err = Err::<(), _>(forbidden)
+            .for_resource("create", "Job", "agent-api-task5-attempt1", "orchestrator")
+            .unwrap_err();
+        assert_eq!(err.api_status(), Some(403));
+        assert!(
+            err.to_string().starts_with(
+                "failed to create Job 'agent-api-task5-attempt1' in ns 'orchestrator': "
+            ),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn test_resource_name_stays_within_limit() {
+        assert_eq!(
+            resource_name("code-agent_platform-Rex.v2-t7-v1"),
+            "code-agent-platform-rex-v2-t7-v1"
+        );
+
+        let service = "an-extraordinarily-verbose-service-name-".repeat(4);
+        let long_a = format!("code-agent-platform-{service}-t1-v1-files");
+        let long_b = format!("code-agent-platform-{service}-t2-v1-files");
+        for raw in [&long_a, &long_b, &"a_".repeat(100), &"é".repeat(100)] {
+            let name = resource_name(raw);
+            assert!(name.len() <= MAX_RESOURCE_NAME_LEN, "{name}");
+            assert!(
+                name.chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-'),
+                "{name}"
+            );
+            assert_eq!(name, resource_name(raw), "names must be deterministic");
+        }
+        // Inputs that only differ past the truncation point must not collide
+        assert_ne!(resource_name(&long_a), resource_name(&long_b));
+    }
+
+    #[test]
+    fn test_job_needs_adoption() {
+        // Controller crashed after creating the Job, before recording it in status
+        assert!(job_needs_adoption(None, "code-job-t1-v1"));
+        assert!(job_needs_adoption(Some("code-job-t0-v1"), "code-job-t1-v1"));
+        assert!(!job_needs_adoption(
+            Some("code-job-t1-v1"),
+            "code-job-t1-v1"
+        ));
+    }
+
+    #[test]
+    fn test_fnv1a_matches_reference_vectors() {
+        // Generated names and stored hashes depend on these staying fixed
+        assert_eq!(fnv1a(*b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(*b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(*b"foobar"), 0x8594_4171_f739_67e8);
+    }
+}
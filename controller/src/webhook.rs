@@ -0,0 +1,370 @@
+//! Admission webhooks for `CodeRun` and `DocsRun` creation
+//!
+//! The validating webhook runs the same spec checks the code controller applies before
+//! starting a Job, so an invalid `CodeRun` is rejected at `kubectl apply` instead of
+//! sitting Failed. The mutating webhook fills spec defaults from the controller config,
+//! so a minimal manifest applied with `kubectl` gets the same defaults as MCP
+//! submissions. The API server only calls webhooks over HTTPS, so the router is served
+//! with the certificate mounted at [`WEBHOOK_CERT_DIR`] (e.g. issued by cert-manager).
+
+use crate::crds::CodeRun;
+use crate::tasks::code::validate_code_run;
+use crate::tasks::config::{ControllerConfig, SpecDefaultsConfig};
+use axum::{extract::State, response::Json, routing::post, Router};
+use axum_server::tls_rustls::RustlsConfig;
+use kube::core::admission::{AdmissionRequest, AdmissionResponse, AdmissionReview};
+use kube::core::DynamicObject;
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Directory holding the webhook's `tls.crt` and `tls.key`
+pub const WEBHOOK_CERT_DIR: &str = "/etc/webhook/certs";
+
+/// Port the HTTPS webhook server listens on
+pub const WEBHOOK_PORT: u16 = 8443;
+
+/// Path the API server posts `CodeRun` admission reviews to for validation
+pub const VALIDATE_CODERUN_PATH: &str = "/validate/coderun";
+
+/// Path the API server posts `CodeRun` and `DocsRun` admission reviews to for defaulting
+pub const MUTATE_PATH: &str = "/mutate";
+
+/// How often the certificate is re-read so rotated certs apply without a restart
+const CERT_RELOAD_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Routes served on the webhook port
+pub fn router(config: Arc<ControllerConfig>) -> Router {
+    Router::new()
+        .route(VALIDATE_CODERUN_PATH, post(validate_code_run_handler))
+        .route(MUTATE_PATH, post(default_spec_handler))
+        .with_state(config)
+}
+
+/// Decode an admission review and answer it with `handle`, rejecting malformed reviews
+fn respond(
+    review: AdmissionReview<DynamicObject>,
+    handle: impl FnOnce(&AdmissionRequest<DynamicObject>) -> AdmissionResponse,
+) -> Json<AdmissionReview<DynamicObject>> {
+    let request: Result<AdmissionRequest<DynamicObject>, _> = review.try_into();
+    let response = match request {
+        Ok(request) => handle(&request),
+        Err(e) => {
+            warn!("Rejecting malformed admission review: {}", e);
+            AdmissionResponse::invalid(e)
+        }
+    };
+    Json(response.into_review())
+}
+
+async fn validate_code_run_handler(
+    State(config): State<Arc<ControllerConfig>>,
+    Json(review): Json<AdmissionReview<DynamicObject>>,
+) -> Json<AdmissionReview<DynamicObject>> {
+    respond(review, |request| review_code_run(request, &config))
+}
+
+async fn default_spec_handler(
+    State(config): State<Arc<ControllerConfig>>,
+    Json(review): Json<AdmissionReview<DynamicObject>>,
+) -> Json<AdmissionReview<DynamicObject>> {
+    respond(review, |request| default_spec(request, &config.defaults))
+}
+
+/// Admit or deny a `CodeRun`, using the controller's own spec validation
+pub fn review_code_run(
+    request: &AdmissionRequest<DynamicObject>,
+    config: &ControllerConfig,
+) -> AdmissionResponse {
+    let response = AdmissionResponse::from(request);
+    let Some(object) = request.object.as_ref() else {
+        return response;
+    };
+
+    let result = match serde_json::to_value(object).and_then(serde_json::from_value::<CodeRun>) {
+        Ok(code_run) => validate_code_run(&code_run.spec, &config.permissions),
+        Err(e) => Err(format!("invalid CodeRun: {e}")),
+    };
+    match result {
+        Ok(()) => response,
+        Err(message) => {
+            info!("Denying CodeRun {}: {}", request.name, message);
+            response.deny(message)
+        }
+    }
+}
+
+/// Patch a new `CodeRun` or `DocsRun` with any spec defaults it omits
+pub fn default_spec(
+    request: &AdmissionRequest<DynamicObject>,
+    defaults: &SpecDefaultsConfig,
+) -> AdmissionResponse {
+    let response = AdmissionResponse::from(request);
+    let Some(object) = request.object.as_ref() else {
+        return response;
+    };
+
+    let operations = spec_default_operations(&request.kind.kind, object.data.get("spec"), defaults);
+    if operations.is_empty() {
+        return response;
+    }
+    let operations = Value::Array(operations);
+    info!(
+        "Defaulting {} {}: {}",
+        request.kind.kind, request.name, operations
+    );
+    match serde_json::from_value(operations) {
+        Ok(patch) => response
+            .clone()
+            .with_patch(patch)
+            .unwrap_or_else(|e| response.deny(e)),
+        Err(e) => response.deny(format!("failed to build defaults patch: {e}")),
+    }
+}
+
+/// JSON patch operations filling the spec fields `kind` leaves unset or empty
+fn spec_default_operations(
+    kind: &str,
+    spec: Option<&Value>,
+    defaults: &SpecDefaultsConfig,
+) -> Vec<Value> {
+    // A missing spec is left for schema validation to report
+    let Some(spec) = spec.and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    let unset = |field: &str| {
+        spec.get(field)
+            .is_none_or(|value| value.is_null() || value.as_str() == Some(""))
+    };
+
+    let mut operations = Vec::new();
+    let model = match kind {
+        "CodeRun" => defaults.code_model.as_deref(),
+        "DocsRun" => defaults.docs_model.as_deref(),
+        _ => None,
+    };
+    if let Some(model) = model.filter(|model| !model.is_empty() && unset("model")) {
+        operations.push(json!({ "op": "add", "path": "/spec/model", "value": model }));
+    }
+    if kind == "CodeRun" && unset("contextVersion") {
+        operations.push(json!({ "op": "add", "path": "/spec/contextVersion", "value": 1 }));
+    }
+    if let Some(template) = defaults.working_branch.as_deref() {
+        if kind == "CodeRun" && !template.is_empty() && unset("workingBranch") {
+            operations
+                .push(json!({ "op": "add", "path": "/spec/workingBranch", "value": template }));
+        }
+    }
+    operations
+}
+
+/// Serve the webhook over HTTPS if a certificate is mounted in `cert_dir`; returns
+/// immediately (webhook disabled) when it isn't
+pub async fn serve(config: Arc<ControllerConfig>, cert_dir: &Path) -> anyhow::Result<()> {
+    let cert = cert_dir.join("tls.crt");
+    let key = cert_dir.join("tls.key");
+    if !cert.exists() || !key.exists() {
+        info!(
+            "No webhook certificate in {}, admission webhook disabled",
+            cert_dir.display()
+        );
+        return Ok(());
+    }
+
+    // ring is the only rustls provider compiled in; a second install attempt is harmless
+    let _ = rustls::crypto::ring::default_provider().install_default();
+    let tls = RustlsConfig::from_pem_file(&cert, &key).await?;
+    tokio::spawn(reload_certificate(tls.clone(), cert, key));
+
+    let addr = SocketAddr::from(([0, 0, 0, 0], WEBHOOK_PORT));
+    info!("Admission webhook listening on https://{}", addr);
+    axum_server::bind_rustls(addr, tls)
+        .serve(router(config).into_make_service())
+        .await?;
+    Ok(())
+}
+
+async fn reload_certificate(tls: RustlsConfig, cert: PathBuf, key: PathBuf) {
+    loop {
+        tokio::time::sleep(CERT_RELOAD_INTERVAL).await;
+        if let Err(e) = tls.reload_from_pem_file(&cert, &key).await {
+            warn!("Failed to reload webhook certificate: {}", e);
+        }
+    }
+}
+
+/// Webhook `clientConfig` pointing at `path` on the controller Service
+fn client_config(namespace: &str, service: &str, path: &str, ca_bundle: Option<&str>) -> Value {
+    let mut client_config = json!({
+        "service": {
+            "name": service,
+            "namespace": namespace,
+            "path": path,
+            "port": WEBHOOK_PORT
+        }
+    });
+    if let Some(ca_bundle) = ca_bundle {
+        client_config["caBundle"] = json!(ca_bundle);
+    }
+    client_config
+}
+
+/// `ValidatingWebhookConfiguration` sending `CodeRun` creates to the controller Service.
+///
+/// `failurePolicy` is `Ignore`: the controller re-validates before starting a Job, so an
+/// unavailable webhook only delays the error rather than blocking all submissions.
+pub fn validating_webhook_configuration(
+    namespace: &str,
+    service: &str,
+    ca_bundle: Option<&str>,
+) -> Value {
+    let client_config = client_config(namespace, service, VALIDATE_CODERUN_PATH, ca_bundle);
+
+    json!({
+        "apiVersion": "admissionregistration.k8s.io/v1",
+        "kind": "ValidatingWebhookConfiguration",
+        "metadata": {
+            "name": "coderun-validation.agents.platform"
+        },
+        "webhooks": [{
+            "name": "validate.coderuns.agents.platform",
+            "admissionReviewVersions": ["v1"],
+            "sideEffects": "None",
+            "failurePolicy": "Ignore",
+            "timeoutSeconds": 10,
+            "clientConfig": client_config,
+            "rules": [{
+                "apiGroups": ["agents.platform"],
+                "apiVersions": ["v1"],
+                "operations": ["CREATE"],
+                "resources": ["coderuns"],
+                "scope": "Namespaced"
+            }]
+        }]
+    })
+}
+
+/// `MutatingWebhookConfiguration` sending `CodeRun` and `DocsRun` creates to the controller
+/// Service for defaulting (`failurePolicy: Ignore`, like the validating webhook)
+pub fn mutating_webhook_configuration(
+    namespace: &str,
+    service: &str,
+    ca_bundle: Option<&str>,
+) -> Value {
+    json!({
+        "apiVersion": "admissionregistration.k8s.io/v1",
+        "kind": "MutatingWebhookConfiguration",
+        "metadata": {
+            "name": "run-defaults.agents.platform"
+        },
+        "webhooks": [{
+            "name": "default.runs.agents.platform",
+            "admissionReviewVersions": ["v1"],
+            "sideEffects": "None",
+            "failurePolicy": "Ignore",
+            "reinvocationPolicy": "Never",
+            "timeoutSeconds": 10,
+            "clientConfig": client_config(namespace, service, MUTATE_PATH, ca_bundle),
+            "rules": [{
+                "apiGroups": ["agents.platform"],
+                "apiVersions": ["v1"],
+                "operations": ["CREATE"],
+                "resources": ["coderuns", "docsruns"],
+                "scope": "Namespaced"
+            }]
+        }]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn review(spec: Value) -> AdmissionRequest<DynamicObject> {
+        let review: AdmissionReview<DynamicObject> = serde_json::from_value(json!({
+            "apiVersion": "admission.k8s.io/v1",
+            "kind": "AdmissionReview",
+            "request": {
+                "uid": "705ab4f5-6393-11e8-b7cc-42010a800002",
+                "kind": { "group": "agents.platform", "version": "v1", "kind": "CodeRun" },
+                "resource": { "group": "agents.platform", "version": "v1", "resource": "coderuns" },
+                "name": "task-1",
+                "namespace": "agent-platform",
+                "operation": "CREATE",
+                "userInfo": {},
+                "object": {
+                    "apiVersion": "agents.platform/v1",
+                    "kind": "CodeRun",
+                    "metadata": { "name": "task-1", "namespace": "agent-platform" },
+                    "spec": spec
+                },
+                "dryRun": false
+            }
+        }))
+        .unwrap();
+        review.try_into().unwrap()
+    }
+
+    #[test]
+    fn test_review_code_run_reuses_spec_validation() {
+        let config = ControllerConfig::default();
+        let mut spec = json!({
+            "taskId": 1,
+            "service": "api",
+            "repositoryUrl": "https://github.com/5dlabs/example",
+            "docsRepositoryUrl": "https://github.com/5dlabs/docs",
+            "model": "claude-sonnet-4-20250514",
+            "githubApp": "5DLabs-Rex"
+        });
+        assert!(review_code_run(&review(spec.clone()), &config).allowed);
+
+        spec["toolsProfile"] = json!("missing");
+        let denied = review_code_run(&review(spec.clone()), &config);
+        assert!(!denied.allowed);
+        assert_eq!(
+            denied.result.message,
+            "toolsProfile 'missing' is not defined (available: none)"
+        );
+
+        // Objects that don't even deserialize are denied with the serde error
+        spec.as_object_mut().unwrap().remove("model");
+        let denied = review_code_run(&review(spec), &config);
+        assert!(!denied.allowed);
+        assert!(denied.result.message.starts_with("invalid CodeRun: "));
+    }
+
+    #[test]
+    fn test_spec_default_operations_fill_only_unset_fields() {
+        let defaults = SpecDefaultsConfig {
+            code_model: Some("claude-sonnet-4-20250514".to_string()),
+            docs_model: None,
+            working_branch: Some("agent/{user}/task-{task_id}".to_string()),
+        };
+
+        let operations = spec_default_operations(
+            "CodeRun",
+            Some(&json!({ "taskId": 1, "model": "" })),
+            &defaults,
+        );
+        assert_eq!(
+            operations,
+            vec![
+                json!({ "op": "add", "path": "/spec/model", "value": "claude-sonnet-4-20250514" }),
+                json!({ "op": "add", "path": "/spec/contextVersion", "value": 1 }),
+                json!({ "op": "add", "path": "/spec/workingBranch", "value": "agent/{user}/task-{task_id}" }),
+            ]
+        );
+
+        let explicit = json!({
+            "model": "claude-opus-4-20250514",
+            "contextVersion": 3,
+            "workingBranch": "feature/{task_id}"
+        });
+        assert!(spec_default_operations("CodeRun", Some(&explicit), &defaults).is_empty());
+        // No configured docs model leaves DocsRuns untouched
+        assert!(spec_default_operations("DocsRun", Some(&json!({})), &defaults).is_empty());
+    }
+}
@@ -37,6 +37,9 @@ echo "  ✓ Project name: $PROJECT_NAME"
 REPOSITORY_URL=$(jq -r '.repository_url' "$CONFIG_FILE" 2>/dev/null || echo "")
 echo "  ✓ Repository URL: $REPOSITORY_URL"
 
+SOURCE_BRANCH=$(jq -r '.source_branch // "main"' "$CONFIG_FILE" 2>/dev/null || echo "main")
+echo "  ✓ Source branch: $SOURCE_BRANCH"
+
 GITHUB_APP=$(jq -r '.github_app' "$CONFIG_FILE" 2>/dev/null || echo "")
 echo "  ✓ GitHub App: $GITHUB_APP"
 
@@ -55,6 +58,7 @@ echo "  ✓ Analyze complexity: $ANALYZE_COMPLEXITY"
 echo "🔍 Configuration summary:"
 echo "  - Project: ${PROJECT_NAME:-[empty]}"
 echo "  - Repository: ${REPOSITORY_URL:-[empty]}"
+echo "  - Source Branch: ${SOURCE_BRANCH:-[empty]}"
 echo "  - GitHub App: ${GITHUB_APP:-[empty]}"
 echo "  - Model: ${MODEL:-[empty]}"
 echo "  - Num Tasks: ${NUM_TASKS:-[empty]}"
@@ -92,6 +96,9 @@ export GIT_TERMINAL_PROMPT=0
 export GIT_ASKPASS=/bin/true
 export SSH_ASKPASS=/bin/true
 
+# Trust the custom CA bundle when the controller mounts one
+. /intake-files/ca-trust.sh
+
 # GitHub App authentication setup
 if [ -n "$GITHUB_APP_PRIVATE_KEY" ] && [ -n "$GITHUB_APP_ID" ]; then
     echo "🔐 Setting up GitHub App authentication..."
@@ -182,9 +189,10 @@ fi
 CLONE_DIR="/tmp/repo-$(date +%s)"
 echo "📂 Clone directory: $CLONE_DIR"
 echo "🔍 Attempting git clone..."
-git clone "$REPOSITORY_URL" "$CLONE_DIR" || {
+git clone --branch "$SOURCE_BRANCH" "$REPOSITORY_URL" "$CLONE_DIR" || {
     echo "❌ Git clone failed with exit code $?"
     echo "Repository URL: $REPOSITORY_URL"
+    echo "Source branch: $SOURCE_BRANCH"
     echo "Clone directory: $CLONE_DIR"
     exit 1
 }
@@ -583,7 +591,7 @@ gh pr create \
     --title "🚀 Project Intake: $PROJECT_NAME" \
     --body "$PR_BODY" \
     --head "$BRANCH_NAME" \
-    --base main || {
+    --base "$SOURCE_BRANCH" || {
         echo "⚠️ Failed to create PR, but branch has been pushed"
         echo "Branch: $BRANCH_NAME"
         echo "You can create the PR manually"
@@ -0,0 +1,18 @@
+#!/bin/sh
+# Trust the custom CA bundle (TLS-intercepting corporate proxies) when the controller mounts one.
+# Sourced by the code, docs and intake entrypoints so the exports reach their processes.
+if [ -n "${CUSTOM_CA_BUNDLE:-}" ] && [ -f "$CUSTOM_CA_BUNDLE" ]; then
+    export NODE_EXTRA_CA_CERTS="$CUSTOM_CA_BUNDLE"
+    if sudo -n cp "$CUSTOM_CA_BUNDLE" /usr/local/share/ca-certificates/custom-ca.crt 2>/dev/null \
+        && sudo -n update-ca-certificates >/dev/null 2>&1; then
+        CA_CERTS_FILE=/etc/ssl/certs/ca-certificates.crt
+        echo "✓ Installed custom CA bundle into the system trust store"
+    else
+        # No root: append the custom CA to a copy of the public roots instead
+        CA_CERTS_FILE=/tmp/ca-certificates.crt
+        cat /etc/ssl/certs/ca-certificates.crt "$CUSTOM_CA_BUNDLE" > "$CA_CERTS_FILE"
+        echo "✓ Using custom CA bundle from $CA_CERTS_FILE (system trust store not writable)"
+    fi
+    export GIT_SSL_CAINFO="$CA_CERTS_FILE"
+    export SSL_CERT_FILE="$CA_CERTS_FILE"
+fi
@@ -8,7 +8,12 @@ pub fn get_tool_schemas() -> Value {
             get_docs_schema(),
             get_task_schema(&HashMap::new()),
             get_export_schema(),
-            get_intake_schema()
+            get_intake_schema(),
+            get_describe_schema(),
+            get_cancel_schema(),
+            get_status_schema(),
+            get_list_schema(),
+            get_doctor_schema()
         ]
     })
 }
@@ -20,7 +25,12 @@ pub fn get_tool_schemas_with_config(agents: &HashMap<String, String>) -> Value {
             get_docs_schema(),
             get_task_schema(agents),
             get_export_schema(),
-            get_intake_schema()
+            get_intake_schema(),
+            get_describe_schema(),
+            get_cancel_schema(),
+            get_status_schema(),
+            get_list_schema(),
+            get_doctor_schema()
         ]
     })
 }
@@ -47,6 +57,31 @@ fn get_docs_schema() -> Value {
                 "include_codebase": {
                     "type": "boolean",
                     "description": "Include existing codebase as markdown context (optional, defaults to false)"
+                },
+                "changed_only": {
+                    "type": "boolean",
+                    "description": "Only regenerate docs for tasks changed in tasks.json since the last successful docs run (optional, defaults to false)"
+                },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "Resolve config defaults and return the Argo parameters without committing local changes or submitting the workflow (optional, defaults to false)"
+                },
+                "taskmaster_dir": {
+                    "type": "string",
+                    "description": "Task Master directory relative to working_directory, for monorepos with a .taskmaster per service (optional, defaults to '.taskmaster')"
+                },
+                "namespace": {
+                    "type": "string",
+                    "description": "Namespace to submit to, e.g. a staging install of the platform (optional, defaults to defaults.namespace in config, else agent-platform)"
+                },
+                "prompt_modification": {
+                    "type": "string",
+                    "description": "Corrective guidance for a retry, e.g. 'the last run missed task 7' (optional)"
+                },
+                "prompt_mode": {
+                    "type": "string",
+                    "enum": ["append", "replace"],
+                    "description": "Whether prompt_modification is appended to the docs prompt or replaces it (optional, defaults to 'append')"
                 }
             },
             "required": ["working_directory"]
@@ -104,6 +139,35 @@ fn get_task_schema(agents: &HashMap<String, String>) -> Value {
                     "type": "boolean",
                     "description": "Whether to continue a previous session (optional, defaults to false)"
                 },
+                "since_failure": {
+                    "type": "boolean",
+                    "description": "When retrying, give the agent the last lines of the previous failed attempt's logs for this task and service (optional, defaults to false)"
+                },
+                "tools_profile": {
+                    "type": "string",
+                    "description": "Named tool permission preset defined in the controller config, e.g. readonly, standard or full (optional, defaults to the controller's permissions)"
+                },
+                "namespace": {
+                    "type": "string",
+                    "description": "Namespace to submit to, e.g. a staging install of the platform (optional, defaults to defaults.namespace in config, else agent-platform)"
+                },
+                "repository_visibility": {
+                    "type": "string",
+                    "enum": ["public", "private"],
+                    "description": "Repository visibility hint; public repositories are cloned anonymously and only need GitHub App credentials to push (optional, detected via the GitHub API when omitted)"
+                },
+                "working_branch": {
+                    "type": "string",
+                    "description": "Feature branch name template, e.g. 'agent/{user}/task-{task_id}'; placeholders are {task_id}, {service}, {user} and {timestamp} (optional, defaults to the controller's configured convention)"
+                },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "Resolve config defaults and return the Argo parameters without submitting the workflow (optional, defaults to false)"
+                },
+                "commit_sha": {
+                    "type": "string",
+                    "description": "Commit SHA (7-40 hex characters) to start the feature branch from instead of the tip of the base branch (optional, for reproducible runs)"
+                },
                 "overwrite_memory": {
                     "type": "boolean",
                     "description": "Whether to overwrite CLAUDE.md memory file (optional, defaults to false)"
@@ -149,7 +213,17 @@ fn get_export_schema() -> Value {
         "description": "Export Rust codebase to markdown for documentation context",
         "inputSchema": {
             "type": "object",
-            "properties": {},
+            "properties": {
+                "max_file_kb": {
+                    "type": "integer",
+                    "description": "Files larger than this are listed without their contents (optional, defaults to 512)"
+                },
+                "extensions": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                    "description": "Extra file extensions to export, mapped to their code fence language, e.g. {\"proto\": \"protobuf\"}. Added to the built-ins and export.extensions from cto-config.json (optional)"
+                }
+            },
             "required": []
         }
     })
@@ -165,9 +239,142 @@ fn get_intake_schema() -> Value {
                 "project_name": {
                     "type": "string",
                     "description": "Name of the project subdirectory containing intake files (required)"
+                },
+                "num_tasks": {
+                    "type": "integer",
+                    "description": "Approximate number of top-level tasks to generate (optional, defaults to 50)",
+                    "minimum": 1,
+                    "maximum": 500
+                },
+                "namespace": {
+                    "type": "string",
+                    "description": "Namespace to submit to, e.g. a staging install of the platform (optional, defaults to defaults.namespace in config, else agent-platform)"
                 }
             },
             "required": ["project_name"]
         }
     })
 }
+
+fn get_describe_schema() -> Value {
+    json!({
+        "name": "describe",
+        "description": "Show a consolidated view of a code task: the latest CodeRun's spec and status, recent Kubernetes events, and the tail of the agent job's logs",
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "task_id": {
+                    "type": "integer",
+                    "description": "Task ID to describe",
+                    "minimum": 1
+                },
+                "service": {
+                    "type": "string",
+                    "description": "Only consider CodeRuns for this service (useful when several services share task IDs)"
+                },
+                "log_lines": {
+                    "type": "integer",
+                    "description": "Number of log lines to include (default 50, max 500)",
+                    "minimum": 0,
+                    "maximum": 500
+                },
+                "bundle_path": {
+                    "type": "string",
+                    "description": "Also write the spec, status, events and logs to this markdown file (relative to the workspace) for sharing; secret-like env values are redacted"
+                },
+                "namespace": {
+                    "type": "string",
+                    "description": "Namespace the runs were submitted to (optional, defaults to defaults.namespace in config, else agent-platform)"
+                }
+            },
+            "required": ["task_id"]
+        }
+    })
+}
+
+fn get_cancel_schema() -> Value {
+    json!({
+        "name": "cancel",
+        "description": "Cancel a code task: mark its unfinished CodeRuns Cancelled so the controller stops, and delete their agent Jobs. Returns the resources removed; cancelling a finished task reports that there is nothing to cancel",
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "task_id": {
+                    "type": "integer",
+                    "description": "Task ID to cancel",
+                    "minimum": 1
+                },
+                "service": {
+                    "type": "string",
+                    "description": "Only cancel CodeRuns for this service (useful when several services share task IDs)"
+                },
+                "namespace": {
+                    "type": "string",
+                    "description": "Namespace the runs were submitted to (optional, defaults to defaults.namespace in config, else agent-platform)"
+                }
+            },
+            "required": ["task_id"]
+        }
+    })
+}
+
+fn get_status_schema() -> Value {
+    json!({
+        "name": "status",
+        "description": "Check on a submitted code task: the latest CodeRun's phase, message, attempts, last update and how long ago it was, plus the Job name and a kubectl command to follow its logs. Reports not_found if the task has no CodeRun",
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "task_id": {
+                    "type": "integer",
+                    "description": "Task ID to check",
+                    "minimum": 1
+                },
+                "service": {
+                    "type": "string",
+                    "description": "Only consider CodeRuns for this service (useful when several services share task IDs)"
+                },
+                "namespace": {
+                    "type": "string",
+                    "description": "Namespace the runs were submitted to (optional, defaults to defaults.namespace in config, else agent-platform)"
+                }
+            },
+            "required": ["task_id"]
+        }
+    })
+}
+
+fn get_list_schema() -> Value {
+    json!({
+        "name": "list",
+        "description": "List CodeRuns and DocsRuns in the platform namespace with task ID, service, phase, attempts and age, newest first. Without a phase filter only unfinished runs are returned",
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "phase": {
+                    "type": "string",
+                    "description": "Only return runs in this phase, e.g. 'Running' or 'Failed' (optional, defaults to all unfinished runs)"
+                },
+                "service": {
+                    "type": "string",
+                    "description": "Only return CodeRuns for this service; DocsRuns have no service and are left out (optional)"
+                },
+                "namespace": {
+                    "type": "string",
+                    "description": "Namespace the runs were submitted to (optional, defaults to defaults.namespace in config, else agent-platform)"
+                }
+            }
+        }
+    })
+}
+
+fn get_doctor_schema() -> Value {
+    json!({
+        "name": "doctor",
+        "description": "Check the local setup (cto-config.json, git remote, argo/kubectl on PATH, cluster access and workflow templates, env vars) and return a checklist with remediation hints. Reported as an error if any critical check fails",
+        "inputSchema": {
+            "type": "object",
+            "properties": {}
+        }
+    })
+}